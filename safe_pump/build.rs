@@ -7,9 +7,14 @@ use std::fs::OpenOptions;
 use std::io::Write;
 use std::path::Path;
 
+#[path = "build/proof_guard.rs"]
+mod proof_guard;
+
 fn main() {
     println!("cargo:rerun-if-changed=Anchor.toml");
 
+    proof_guard::guard_proof_verification();
+
     let anchor_toml = std::fs::read_to_string("Anchor.toml").expect("Failed to read Anchor.toml");
 
     let mut mothership_id = None;
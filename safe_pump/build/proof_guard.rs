@@ -0,0 +1,170 @@
+// Shared by build.rs and tests/proof_guard.rs, so the guard itself stays under test.
+
+use std::env;
+use std::path::{Path, PathBuf};
+
+// Crates whose proof verification guards user funds; overriding any of them is never
+// acceptable outside of tests
+const SECURITY_CRITICAL_CRATES: &[&str] = &[
+    "solana-zk-sdk",
+    "solana-zk-token-sdk",
+    "spl-token-2022",
+    "spl-token-confidential-transfer-proof-extraction",
+    "spl-token-confidential-transfer-proof-generation",
+];
+
+const CRATES_IO_SOURCE: &str = "source = \"registry+https://github.com/rust-lang/crates.io-index\"";
+
+const MANIFESTS: &[&str] = &["Cargo.toml", "../Cargo.toml", ".cargo/config.toml", "../.cargo/config.toml"];
+
+/// Fails the build if a security-critical crate is patched or path-overridden, or if
+/// any local dependency ships a `verify_proof` that accepts everything. Only the
+/// `insecure-proof-stubs` feature lets this through, and never in a release build.
+pub fn guard_proof_verification() {
+    // Inside the workspace cargo resolves against the root lockfile, not ours
+    let in_workspace = std::fs::read_to_string("../Cargo.toml").is_ok_and(|s| s.contains("[workspace]"));
+    let lockfile = if in_workspace { "../Cargo.lock" } else { "Cargo.lock" };
+
+    println!("cargo:rerun-if-env-changed=PROFILE");
+    for file in MANIFESTS.iter().chain([&lockfile]) {
+        if Path::new(file).exists() {
+            println!("cargo:rerun-if-changed={}", file);
+        }
+    }
+
+    let stubs_allowed = env::var_os("CARGO_FEATURE_INSECURE_PROOF_STUBS").is_some();
+    let profile = env::var("PROFILE").unwrap_or_default();
+    if stubs_allowed && profile == "release" {
+        panic!(
+            "safe_pump: the `insecure-proof-stubs` feature cannot be enabled in a release build; \
+             it allows forged zero-knowledge proofs to verify"
+        );
+    }
+
+    let mut findings = Vec::new();
+    for manifest in MANIFESTS {
+        let Ok(contents) = std::fs::read_to_string(manifest) else { continue };
+        let base = Path::new(manifest).parent().unwrap_or(Path::new("")).to_path_buf();
+        scan_manifest(manifest, &contents, &base, &mut findings);
+    }
+    if let Ok(contents) = std::fs::read_to_string(lockfile) {
+        scan_lockfile(lockfile, &contents, &mut findings);
+    }
+
+    if findings.is_empty() {
+        return;
+    }
+    if stubs_allowed {
+        for finding in &findings {
+            println!("cargo:warning=insecure-proof-stubs: {}", finding);
+        }
+        return;
+    }
+    panic!(
+        "safe_pump: refusing to build against overridden proof verification:\n  - {}\n\
+         Remove the override, or enable the `insecure-proof-stubs` feature for a local test build.",
+        findings.join("\n  - ")
+    );
+}
+
+/// Flags `[patch.*]` entries for critical crates and path dependencies (in any table)
+/// whose sources contain a no-op `verify_proof`.
+pub fn scan_manifest(name: &str, contents: &str, base: &Path, findings: &mut Vec<String>) {
+    let mut in_patch = false;
+    for line in contents.lines() {
+        let line = line.split('#').next().unwrap_or("").trim();
+        if line.starts_with('[') {
+            in_patch = line.starts_with("[patch");
+            // `[patch.crates-io.spl-token-2022]` style tables
+            if in_patch {
+                if let Some(krate) = SECURITY_CRITICAL_CRATES.iter().find(|c| line.trim_matches(|ch| ch == '[' || ch == ']').ends_with(&format!(".{}", c))) {
+                    findings.push(format!("{} patches `{}`", name, krate));
+                }
+            }
+            continue;
+        }
+        let Some((key, value)) = line.split_once('=') else { continue };
+        let key = key.trim().trim_matches('"');
+        if in_patch && SECURITY_CRITICAL_CRATES.contains(&key) {
+            findings.push(format!("{} patches `{}`", name, key));
+        }
+        if let Some(path) = inline_path(value) {
+            let dir = base.join(path);
+            if let Some(file) = find_noop_verify_proof(&dir) {
+                findings.push(format!("`{}` ({}) has a no-op verify_proof in {}", key, name, file.display()));
+            }
+        }
+    }
+}
+
+/// A critical crate must resolve from crates.io: no `source` line means a local path,
+/// and a git or alternate-registry source is just as unreviewed.
+pub fn scan_lockfile(name: &str, contents: &str, findings: &mut Vec<String>) {
+    for package in contents.split("[[package]]").skip(1) {
+        let Some(krate) = package
+            .lines()
+            .find_map(|l| l.trim().strip_prefix("name = "))
+            .map(|n| n.trim_matches('"'))
+        else {
+            continue;
+        };
+        if SECURITY_CRITICAL_CRATES.contains(&krate) && !package.lines().any(|l| l.trim() == CRATES_IO_SOURCE) {
+            findings.push(format!("{} resolves `{}` from outside crates.io", name, krate));
+        }
+    }
+}
+
+fn inline_path(value: &str) -> Option<&str> {
+    let rest = value.split("path").nth(1)?;
+    let rest = rest.trim_start().strip_prefix('=')?;
+    rest.split('"').nth(1)
+}
+
+/// Walks `dir` for a `fn verify_proof` whose body is just `Ok(..)` of a value it
+/// never computed.
+pub fn find_noop_verify_proof(dir: &Path) -> Option<PathBuf> {
+    let entries = std::fs::read_dir(dir).ok()?;
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.file_name().is_some_and(|n| n == "target" || n == ".git") {
+            continue;
+        }
+        if path.is_dir() {
+            if let Some(found) = find_noop_verify_proof(&path) {
+                return Some(found);
+            }
+        } else if path.extension().is_some_and(|e| e == "rs") {
+            let Ok(source) = std::fs::read_to_string(&path) else { continue };
+            if has_noop_verify_proof(&source) {
+                return Some(path);
+            }
+        }
+    }
+    None
+}
+
+pub fn has_noop_verify_proof(source: &str) -> bool {
+    let mut rest = source;
+    while let Some(idx) = rest.find("fn verify_proof") {
+        rest = &rest[idx..];
+        let Some(open) = rest.find('{') else { return false };
+        let body: String = rest[open + 1..]
+            .chars()
+            .take_while(|c| *c != '}')
+            .filter(|c| !c.is_whitespace())
+            .collect();
+        if is_noop_body(&body) {
+            return true;
+        }
+        rest = &rest[open..];
+    }
+    false
+}
+
+/// `Ok(())`, `Ok(<Path>::default())`, or `Ok(name)` / `Ok($name)` as macro stubs write it.
+/// `body` has its whitespace stripped.
+fn is_noop_body(body: &str) -> bool {
+    let Some(value) = body.strip_prefix("Ok(").and_then(|b| b.strip_suffix(')')) else { return false };
+    let is_path = |s: &str| !s.is_empty() && s.chars().all(|c| c.is_alphanumeric() || matches!(c, '_' | ':' | '<' | '>'));
+    value == "()" || value.strip_suffix("::default()").is_some_and(is_path) || is_path(value.strip_prefix('$').unwrap_or(value))
+}
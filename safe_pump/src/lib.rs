@@ -4,15 +4,26 @@
 use anchor_lang::prelude::*;
 use anchor_spl::{
     associated_token::{self, AssociatedToken},
-    token::{self, Mint, Token, TokenAccount, Transfer, Burn, MintTo},
+    metadata::{
+        self, mpl_token_metadata::types::DataV2, CreateMasterEditionV3, CreateMetadataAccountsV3,
+        Metadata, MetadataAccount, MintNewEditionFromMasterEditionViaToken,
+    },
+    token::{self, spl_token::native_mint, Mint, Token, TokenAccount, Transfer, Burn, MintTo},
+    token_2022::{self, spl_token_2022::{self, extension::{ExtensionType, StateWithExtensions}}, FreezeAccount, InitializeMint2, Token2022},
+    token_2022_extensions::{non_transferable_mint_initialize, NonTransferableMintInitialize},
+    token_interface,
 };
-use anchor_lang::solana_program::{program::invoke, clock::Clock, sysvar::Sysvar};
+use anchor_lang::system_program::{self, CreateAccount};
+use anchor_lang::solana_program::{program_option::COption, clock::Clock, sysvar::{self, Sysvar}};
+use std::cell::RefMut;
 use raydium_cp_swap::cpi::{accounts::{CreatePool, SwapBaseIn}, create_pool};
 use raydium_cp_swap::instruction::SwapBaseInput;
 
-// BLS12-381
-use blstrs::{G1Affine, G1Projective, G2Projective};
-use subtle::CtOption;
+use safe_pump_interface::swap_authorization::{SwapAuthorization, SWAP_AUTHORIZATION_DST};
+use safe_pump_interface::swap_authorizer::{VaultAuthKey, VAULT_AUTH_KEY_LEN};
+use safe_pump_interface::vault_key::{vault_key_pop_message, vault_key_rotation_message, VAULT_KEY_POP_DST, VAULT_KEY_ROTATION_DST};
+use solana_zk_sdk::zk_elgamal_proof_program::proof_data::{PubkeyValidityProofContext, PubkeyValidityProofData};
+use crate::zk_proof::ProofLocation;
 
 // PROGRAM ID — INJECTED VIA build.rs
 declare_id!(CymD4HzxTN2SK6UDrCcXD2uAFk4RptvQKzMT5P9GSr32(concat!(env!("OUT_DIR"), "/generated_program_ids.rs"));
@@ -22,11 +33,31 @@ pub const MOTHERSHIP_PROGRAM_ID_PUBKEY: Pubkey = MOTHERSHIP_PUBKEY;
 // ─────────────────────────────────────────────────────────────────────────────
 // CONSTANTS & CONFIG
 // ─────────────────────────────────────────────────────────────────────────────
+// Launch defaults for the TaxSchedule account — live values are read on-chain
 const GLOBAL_TAX_BPS: u64 = 250;
 const GLOBAL_LP_TAX_BPS: u64 = 100;        // 1.00%
 const SWAPPER_REWARD_TAX_BPS: u64 = 80;   // 0.80%
 const BADGE_REWARD_TAX_BPS: u64 = 20;     // 0.20%
 const TREASURY_TAX_BPS: u64 = 50;         // 0.50%
+const MAX_GLOBAL_TAX_BPS: u64 = 1_000;    // 10.00% hard ceiling
+const TAX_SCHEDULE_TIMELOCK: i64 = 172_800; // 48h notice before a new split goes live
+const MAX_COIN_TAX_BPS: u64 = 500;        // 5.00% ceiling for per-coin overrides
+
+const REGISTRY_PAGE_SIZE: usize = 256;
+const MAX_APPROVED_CHILD_PROGRAMS: usize = 16;
+const MAX_COUNCIL_MEMBERS: usize = 10;
+
+// GlobalState as first deployed, and with the appended admin/withdrawal/keeper fields
+const LEGACY_GLOBAL_STATE_LEN: usize = 8 + 1 + 32 + 8 + 8 + 8 + 1;
+const GLOBAL_STATE_LEN: usize = LEGACY_GLOBAL_STATE_LEN + 32 + (1 + 32) + 8 + 8 + 8 + 8 + 8 + 8 + (1 + 8 + 8) + 8 + 32 + (1 + 32) + 8 + (4 + 32 * MAX_APPROVED_CHILD_PROGRAMS);
+
+const DEFAULT_WITHDRAWAL_DELAY: i64 = 172_800;                      // 48h between queue and execute
+const MIN_WITHDRAWAL_DELAY: i64 = 86_400;                           // admin can never go below 24h
+const DEFAULT_EPOCH_WITHDRAWAL_CAP: u64 = 100 * LAMPORTS_PER_SOL;   // per Solana epoch
+
+const DEFAULT_KEEPER_FEE_BPS: u64 = 10;  // 0.10% of each crank batch's payouts
+const MAX_KEEPER_FEE_BPS: u64 = 100;
+const MAX_CRANK_BATCH: usize = 20;       // badge holders settled per crank_rewards call
 
 const LAMPORTS_PER_SOL: u64 = 1_000_000_000;
 
@@ -39,8 +70,17 @@ const FIB_MCAP_THRESHOLDS_LAMPORTS: [u64; 8] = [
 ];
 
 const MAX_BADGE_HOLDERS: usize = 1000;
-const BUY_SWAPS_FOR_BADGE: u64 = 1000;
+const BADGE_HOLDER_PAGE_SIZE: usize = 256; // keeps each BadgeHolderPage under the 10KB CPI account-creation limit
+// Launch defaults for the BadgeConfig account: bronze, silver, gold
+const DEFAULT_BADGE_TIERS: [BadgeTier; BADGE_TIER_COUNT] = [
+    BadgeTier { min_buy_swaps: 100, min_buy_volume: 10 * LAMPORTS_PER_SOL, weight: 1 },
+    BadgeTier { min_buy_swaps: 500, min_buy_volume: 100 * LAMPORTS_PER_SOL, weight: 2 },
+    BadgeTier { min_buy_swaps: 1_000, min_buy_volume: 1_000 * LAMPORTS_PER_SOL, weight: 4 },
+];
+const BADGE_TIER_COUNT: usize = 3;
+const DEFAULT_BADGE_EXPIRY: i64 = 0; // seconds without a buy before a badge lapses; 0 = never
 const REWARD_DISTRIBUTION_PERIOD: i64 = 86_400;
+const REWARD_PRECISION: u128 = 1_000_000_000_000; // acc_reward_per_badge scale
 const ANTI_SNIPER_COOLDOWN: i64 = 120;
 const SWAP_COOLDOWN: i64 = 86_400;
 
@@ -53,10 +93,34 @@ const MAX_AIRDROP_CLAIMERS: usize = 10_000;
 const AIRDROP_TRIGGER_COUNT: usize = 1000;
 
 // SEEDS
-const VAULT_SEED: &[u8] = b"zk_vault";
-const MEME_REGISTRY_SEED: &[u8] = b"meme_registry";
-const BADGE_EDITION_SEED: &[u8] = b"badge_master_edition";
+pub const VAULT_SEED: &[u8] = b"zk_vault";
+pub const MEME_REGISTRY_SEED: &[u8] = b"meme_registry"; // legacy single-account registry
+pub const REGISTRY_INDEX_SEED: &[u8] = b"registry_index";
+pub const REGISTRY_PAGE_SEED: &[u8] = b"registry_page";
+pub const REGISTRY_ENTRY_SEED: &[u8] = b"registry_entry";
+pub const CHILD_AUTHORITY_SEED: &[u8] = b"child_authority"; // PDA under the child program
+const CONTRACT_SEED: &[u8] = b"contract";
+pub const ADMIN_COUNCIL_SEED: &[u8] = b"admin_council";
+const WITHDRAWAL_SEED: &[u8] = b"withdrawal";
+pub const REWARDS_SEED: &[u8] = b"rewards";
+pub const REWARDS_VAULT_SEED: &[u8] = b"rewards_vault";
+pub const TREASURY_VAULT_SEED: &[u8] = b"treasury";  // + meme mint
+pub const LP_TAX_VAULT_SEED: &[u8] = b"lp_tax";      // + meme mint
+pub const REWARD_ACCRUAL_SEED: &[u8] = b"reward_accrual"; // + user + epoch
+pub const REWARD_EPOCH_SEED: &[u8] = b"reward_epoch";     // + epoch
+pub const BADGE_RECORD_SEED: &[u8] = b"badge_record";  // + badge mint
+pub const BUY_COUNTER_SEED: &[u8] = b"buy_counter";    // + meme mint + user
+pub const SOULBOUND_BADGE_SEED: &[u8] = b"soulbound_badge"; // + meme mint + user + badges_revoked
+pub const BADGE_CONFIG_SEED: &[u8] = b"badge_config";
+pub const BADGE_HOLDERS_SEED: &[u8] = b"badge_holders"; // + meme mint
+pub const BADGE_HOLDER_PAGE_SEED: &[u8] = b"badge_holder_page"; // + meme mint + page
+pub const BADGE_MINT_SEED: &[u8] = b"badge"; // + meme mint + user + badges_revoked
+pub const BADGE_EDITION_SEED: &[u8] = b"badge_master_edition"; // + meme mint: the master badge mint
+const BADGE_NAME_SUFFIX: &str = " Badge";
+const BADGE_SYMBOL_PREFIX: &str = "B";
 const MEME_MINT_SUFFIX: &str = "SPMP";
+pub const TAX_SCHEDULE_SEED: &[u8] = b"tax_schedule";
+pub const ELGAMAL_KEY_SEED: &[u8] = b"elgamal_key"; // + user
 
 // ─────────────────────────────────────────────────────────────────────────────
 // ACCOUNT STRUCTS
@@ -69,6 +133,156 @@ pub struct GlobalState {
     pub swap_count: u64,
     pub bond_timestamp: i64,
     pub bump: u8,
+    // Everything below was appended to the original layout; see migrate_global_state
+    pub admin: Pubkey,                 // plain key or the AdminCouncil PDA
+    pub pending_admin: Option<Pubkey>, // set by propose_admin, cleared by accept_admin
+    pub withdrawal_delay: i64,
+    pub epoch_withdrawal_cap: u64,
+    pub withdrawal_epoch: u64,
+    pub withdrawn_this_epoch: u64,
+    pub next_withdrawal_id: u64,
+    pub keeper_fee_bps: u64,           // cut of crank payouts paid to the caller
+    pub pending_withdrawal_limits: Option<WithdrawalLimits>, // loosened limits waiting out the current delay
+    pub withdrawal_limits_activate_at: i64,
+    pub guardian: Pubkey,              // may cancel queued withdrawals; plain key or AdminCouncil PDA
+    pub pending_guardian: Option<Pubkey>, // queued by the admin, cancellable by the current guardian
+    pub guardian_activates_at: i64,
+    pub approved_child_programs: Vec<Pubkey>, // child binaries allowed to handshake and trade
+}
+
+impl GlobalState {
+    pub fn is_child_approved(&self, child_program_id: &Pubkey) -> bool {
+        self.approved_child_programs.contains(child_program_id)
+    }
+
+    /// Limits in force at `now`; pending looser limits take over once their timelock has passed.
+    pub fn withdrawal_limits(&self, now: i64) -> WithdrawalLimits {
+        match self.pending_withdrawal_limits {
+            Some(pending) if now >= self.withdrawal_limits_activate_at => pending,
+            _ => WithdrawalLimits { withdrawal_delay: self.withdrawal_delay, epoch_withdrawal_cap: self.epoch_withdrawal_cap },
+        }
+    }
+
+    /// Guardian in force at `now`; an admin-queued replacement takes over once its timelock has passed.
+    pub fn guardian(&self, now: i64) -> Pubkey {
+        match self.pending_guardian {
+            Some(pending) if now >= self.guardian_activates_at => pending,
+            _ => self.guardian,
+        }
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct WithdrawalLimits {
+    pub withdrawal_delay: i64,
+    pub epoch_withdrawal_cap: u64,
+}
+
+/// Per-bucket split of the global swap tax. Buckets must sum to `total_bps`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, Debug, PartialEq, Eq)]
+pub struct TaxSplit {
+    pub total_bps: u64,
+    pub lp_bps: u64,
+    pub swapper_reward_bps: u64,
+    pub badge_reward_bps: u64,
+    pub treasury_bps: u64,
+}
+
+impl TaxSplit {
+    pub fn is_valid(&self) -> bool {
+        let sum = [self.lp_bps, self.swapper_reward_bps, self.badge_reward_bps, self.treasury_bps]
+            .iter()
+            .try_fold(0u64, |acc, bps| acc.checked_add(*bps));
+        self.total_bps <= MAX_GLOBAL_TAX_BPS && sum == Some(self.total_bps)
+    }
+
+    pub fn total_tax(&self, amount_in: u64) -> Result<u64> {
+        let tax = (amount_in as u128)
+            .checked_mul(self.total_bps as u128)
+            .ok_or(SafePumpError::MathError)?
+            / 10_000;
+        Ok(tax as u64)
+    }
+
+    /// Bucket share of an already-computed total tax.
+    pub fn share(&self, total_tax: u64, bucket_bps: u64) -> Result<u64> {
+        if self.total_bps == 0 {
+            return Ok(0);
+        }
+        let share = (total_tax as u128)
+            .checked_mul(bucket_bps as u128)
+            .ok_or(SafePumpError::MathError)?
+            / self.total_bps as u128;
+        Ok(share as u64)
+    }
+
+    /// Splits `total_tax` into (lp, swapper, badge, treasury). Each bucket rounds
+    /// down and treasury takes the dust, so the four always add up to `total_tax`.
+    pub fn shares(&self, total_tax: u64) -> Result<(u64, u64, u64, u64)> {
+        let lp = self.share(total_tax, self.lp_bps)?;
+        let swapper = self.share(total_tax, self.swapper_reward_bps)?;
+        let badge = self.share(total_tax, self.badge_reward_bps)?;
+        let treasury = lp
+            .checked_add(swapper)
+            .and_then(|sum| sum.checked_add(badge))
+            .and_then(|sum| total_tax.checked_sub(sum))
+            .ok_or(SafePumpError::MathError)?;
+        Ok((lp, swapper, badge, treasury))
+    }
+
+    /// What is left for the actual swap once the tax has been taken.
+    pub fn net_amount(&self, amount_in: u64) -> Result<u64> {
+        Ok(amount_in.checked_sub(self.total_tax(amount_in)?).ok_or(SafePumpError::MathError)?)
+    }
+}
+
+#[account]
+pub struct TaxSchedule {
+    pub active: TaxSplit,
+    pub pending: Option<TaxSplit>,
+    pub activates_at: i64,
+    pub bump: u8,
+}
+
+impl TaxSchedule {
+    /// Split in force at `now`; a pending split takes over once its timelock has passed.
+    pub fn effective(&self, now: i64) -> TaxSplit {
+        match self.pending {
+            Some(pending) if now >= self.activates_at => pending,
+            _ => self.active,
+        }
+    }
+}
+
+/// Treasury withdrawal waiting out `GlobalState.withdrawal_delay`.
+#[account]
+pub struct WithdrawalRequest {
+    pub id: u64,
+    pub meme_mint: Pubkey, // selects the `[TREASURY_VAULT_SEED, meme_mint]` vault
+    pub amount: u64,
+    pub destination: Pubkey,
+    pub queued_at: i64,
+    pub executable_at: i64,
+    pub payer: Pubkey, // receives the rent back on execute/cancel
+    pub bump: u8,
+}
+
+/// M-of-N signer set that can stand in as `GlobalState.admin`.
+#[account]
+pub struct AdminCouncil {
+    pub members: Vec<Pubkey>,
+    pub threshold: u8,
+    pub bump: u8,
+}
+
+impl AdminCouncil {
+    fn validate(members: &[Pubkey], threshold: u8) -> Result<()> {
+        require!(!members.is_empty() && members.len() <= MAX_COUNCIL_MEMBERS, SafePumpError::InvalidCouncil);
+        require!(threshold >= 1 && threshold as usize <= members.len(), SafePumpError::InvalidCouncil);
+        let unique = members.iter().enumerate().all(|(i, m)| !members[..i].contains(m));
+        require!(unique, SafePumpError::InvalidCouncil);
+        Ok(())
+    }
 }
 
 #[account]
@@ -76,31 +290,272 @@ pub struct Vault {
     pub bump: u8,
     pub nonce: u64,
     pub last_signer: Pubkey,
+    /// Key every swap authorization must be signed with
+    pub auth_key: VaultAuthKey,
 }
 
+/// ElGamal public key confidential payouts to `user` are encrypted to. Only written
+/// after a verified PubkeyValidity proof, so it always has a known secret key.
 #[account]
+pub struct ElGamalKey {
+    pub user: Pubkey,
+    pub pubkey: [u8; 32],
+    pub bump: u8,
+}
+
+/// One per meme coin, created at handshake. Holds the coin's badge reward accumulator
+/// and the bookkeeping for its holder list, whose keys live in `BadgeHolderPage`s, so
+/// each coin's badge economy is isolated. The accumulator is stored as LE bytes: a u128
+/// field is 16-aligned on host targets, which the 8-aligned account data can't satisfy.
+#[account(zero_copy)]
 pub struct BadgeHolders {
-    pub holders: [Pubkey; MAX_BADGE_HOLDERS],
-    pub buy_swap_count: [(Pubkey, u64); MAX_BADGE_HOLDERS],
+    pub meme_mint: Pubkey,
+    pub acc_reward_per_badge: [u8; 16], // u128 per unit of badge weight, scaled by REWARD_PRECISION
     pub holder_count: u64,
+    pub total_badge_weight: u64,      // sum of live BadgeRecord weights
+    pub crank_cursor: u64,            // next holder index for crank_rewards
+    pub cranked_epoch: u64,           // reward epoch + 1 of the last finished crank pass
+    pub bump: u8,
+    pub _padding: [u8; 7],
+}
+
+impl BadgeHolders {
+    /// Page the next minted holder is appended to.
+    pub fn next_page(&self) -> u64 {
+        self.holder_count / BADGE_HOLDER_PAGE_SIZE as u64
+    }
+
+    /// Page holding the last holder; page 0 while there are none.
+    pub fn last_page(&self) -> u64 {
+        self.holder_count.saturating_sub(1) / BADGE_HOLDER_PAGE_SIZE as u64
+    }
+
+    /// Page crank_rewards works through next.
+    pub fn crank_page(&self) -> u64 {
+        self.crank_cursor.min(self.holder_count) / BADGE_HOLDER_PAGE_SIZE as u64
+    }
+
+    /// Page holding the last holder already settled this crank pass; page 0 between passes.
+    pub fn settled_page(&self) -> u64 {
+        self.crank_cursor.saturating_sub(1) / BADGE_HOLDER_PAGE_SIZE as u64
+    }
+
+    pub fn acc_reward_per_badge(&self) -> u128 {
+        u128::from_le_bytes(self.acc_reward_per_badge)
+    }
+
+    /// Spreads `amount` over every live badge by tier weight. Callers route the badge
+    /// share elsewhere while no badge exists, so nothing is ever owed to a later minter.
+    pub fn accrue_badge_rewards(&mut self, amount: u64) -> Result<()> {
+        if amount == 0 {
+            return Ok(());
+        }
+        let per_weight = (amount as u128)
+            .checked_mul(REWARD_PRECISION)
+            .and_then(|scaled| scaled.checked_div(self.total_badge_weight as u128))
+            .ok_or(SafePumpError::MathError)?;
+        let acc = self.acc_reward_per_badge().checked_add(per_weight).ok_or(SafePumpError::MathError)?;
+        self.acc_reward_per_badge = acc.to_le_bytes();
+        Ok(())
+    }
+
+    pub fn pending_badge_reward(&self, reward_debt: u128, weight: u64) -> Result<u64> {
+        let owed = self.acc_reward_per_badge()
+            .checked_sub(reward_debt)
+            .and_then(|d| d.checked_mul(weight as u128))
+            .ok_or(SafePumpError::MathError)?
+            / REWARD_PRECISION;
+        u64::try_from(owed).map_err(|_| error!(SafePumpError::MathError))
+    }
+}
+
+/// `BADGE_HOLDER_PAGE_SIZE` consecutive slots of a coin's holder list, holder `i` at
+/// page `i / BADGE_HOLDER_PAGE_SIZE`. Page 0 is created at handshake, later ones by
+/// the mint that first needs them. Zero-copy: at ~8KB it does not fit on the stack.
+#[account(zero_copy)]
+pub struct BadgeHolderPage {
+    pub meme_mint: Pubkey,
+    pub page: u64,
     pub bump: u8,
+    pub _padding: [u8; 7],
+    pub holders: [Pubkey; BADGE_HOLDER_PAGE_SIZE], // appended by the mint paths only
+}
+
+/// Buy swaps a user has made on one coin, counted toward badge eligibility.
+#[account]
+pub struct BuyCounter {
+    pub meme_mint: Pubkey,
+    pub user: Pubkey,
+    pub buy_swaps: u64,
+    pub buy_volume: u64,      // cumulative SOL in, lamports
+    pub last_buy_at: i64,
+    pub badges_revoked: u64,  // salts the badge mint PDA so a revoked badge can be re-earned
+    pub bump: u8,
+}
+
+/// Entry requirements and reward weight for one badge tier.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BadgeTier {
+    pub min_buy_swaps: u64,
+    pub min_buy_volume: u64,
+    pub weight: u64,
+}
+
+/// Admin-managed badge tiers, ordered bronze, silver, gold.
+#[account]
+pub struct BadgeConfig {
+    pub tiers: [BadgeTier; BADGE_TIER_COUNT],
+    pub expiry_secs: i64,     // 0 disables revoke_inactive_badge
+    pub bump: u8,
+}
+
+impl BadgeConfig {
+    /// Tiers must be non-decreasing in every field and carry a non-zero weight.
+    pub fn is_valid(tiers: &[BadgeTier; BADGE_TIER_COUNT]) -> bool {
+        tiers.iter().all(|t| t.weight > 0)
+            && tiers.windows(2).all(|w| {
+                w[1].min_buy_swaps >= w[0].min_buy_swaps
+                    && w[1].min_buy_volume >= w[0].min_buy_volume
+                    && w[1].weight >= w[0].weight
+            })
+    }
+
+    /// Highest tier the buy history qualifies for, if any.
+    pub fn tier_for(&self, counter: &BuyCounter) -> Option<u8> {
+        self.tiers
+            .iter()
+            .rposition(|t| counter.buy_swaps >= t.min_buy_swaps && counter.buy_volume >= t.min_buy_volume)
+            .map(|i| i as u8)
+    }
+
+    pub fn is_expired(&self, counter: &BuyCounter, now: i64) -> bool {
+        self.expiry_secs > 0 && now.saturating_sub(counter.last_buy_at) >= self.expiry_secs
+    }
 }
 
 #[account]
 pub struct RewardDistribution {
-    pub swapper_rewards: [(Pubkey, u64); MAX_BADGE_HOLDERS],
-    pub badge_rewards: u64,
+    pub swapper_rewards_accrued: u64, // credited to RewardAccrual PDAs this period
+    pub badge_rewards: u64,           // badge tax collected this period
     pub swap_count: u64,
     pub last_distribution_timestamp: i64,
+    pub current_epoch: u64,
+    pub participants: u64,            // distinct swappers this epoch
+    pub bump: u8,
+}
+
+/// One per minted badge. `reward_debt` is the accumulator value the badge has
+/// already been paid up to, so a badge minted mid-period only earns from then on.
+#[account]
+pub struct BadgeRecord {
+    pub badge_mint: Pubkey,
+    pub meme_mint: Pubkey,
+    pub owner: Pubkey,        // original earner; only they can claim
+    pub reward_debt: u128,
+    pub claimed: u64,
+    pub soulbound: bool,
+    pub tier: u8,             // index into BadgeConfig.tiers at mint time
+    pub weight: u64,
+    pub bump: u8,
+}
+
+/// Per-user, per-epoch swapper reward ledger. Claimable once its epoch is closed.
+#[account]
+pub struct RewardAccrual {
+    pub user: Pubkey,
+    pub epoch: u64,
+    pub accrued: u64,
+    pub claimed: bool,
+    pub bump: u8,
+}
+
+/// Snapshot written when a reward epoch closes. Never mutated afterwards except
+/// for the running `swapper_claimed` total.
+#[account]
+pub struct RewardEpoch {
+    pub epoch: u64,
+    pub started_at: i64,
+    pub distributed_at: i64,
+    pub swap_count: u64,
+    pub participants: u64,
+    pub swapper_rewards: u64,
+    pub badge_rewards: u64,
+    pub swapper_claimed: u64,
     pub bump: u8,
 }
 
+/// Legacy single-account registry. Only read by `migrate_registry_entry`.
 #[account]
 pub struct MemeCoinRegistry {
     pub entries: Vec<(Pubkey, Pubkey)>, // (meme_mint, child_program_id)
     pub bump: u8,
 }
 
+/// Registry header: total coins and which index page is being filled.
+#[account]
+pub struct RegistryIndex {
+    pub entry_count: u64,
+    pub migrated_count: u64,
+    pub bump: u8,
+}
+
+impl RegistryIndex {
+    pub fn current_page(&self) -> u64 {
+        self.entry_count / REGISTRY_PAGE_SIZE as u64
+    }
+}
+
+/// Fixed-size page of registered mints, for page-by-page enumeration. Zero-copy:
+/// at ~8KB it does not fit on the stack.
+#[account(zero_copy)]
+pub struct RegistryPage {
+    pub page: u64,
+    pub len: u16,
+    pub bump: u8,
+    pub _padding: [u8; 5],
+    pub mints: [Pubkey; REGISTRY_PAGE_SIZE],
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CoinStatus {
+    Active,
+    Paused,
+    Delisted, // terminal
+}
+
+/// One PDA per registered meme mint.
+#[account]
+pub struct RegistryEntry {
+    pub meme_mint: Pubkey,
+    pub child_program_id: Pubkey,
+    pub tax_override: Option<TaxSplit>, // None = follow the global TaxSchedule
+    pub index: u64,
+    pub deployer: Pubkey,
+    pub registered_slot: u64,
+    pub registered_at: i64,
+    pub bonded: bool,
+    pub raydium_pool: Pubkey,
+    pub status: CoinStatus,
+    pub pending_tax_override: Option<Option<TaxSplit>>, // Some(None) queues a return to the global schedule
+    pub tax_override_activates_at: i64,
+    pub bump: u8,
+}
+
+impl RegistryEntry {
+    /// Override in force at `now`; a pending change takes over once its timelock has passed.
+    pub fn effective_tax_override(&self, now: i64) -> Option<TaxSplit> {
+        match self.pending_tax_override {
+            Some(pending) if now >= self.tax_override_activates_at => pending,
+            _ => self.tax_override,
+        }
+    }
+
+    /// Tax split charged on trades of this coin: its override if set, otherwise the global schedule.
+    pub fn tax_split(&self, schedule: &TaxSchedule, now: i64) -> TaxSplit {
+        self.effective_tax_override(now).unwrap_or_else(|| schedule.effective(now))
+    }
+}
+
 #[account]
 pub struct BlockSwapState {
     pub total_bought_sol: u64,
@@ -121,7 +576,7 @@ pub struct AirdropClaimRecord {
 }
 
 #[account]
-#[derive(Copy)]
+#[derive(Copy, InitSpace)]
 pub struct TokenContract {
     pub is_initialized: bool,
     pub total_supply: u64,
@@ -139,8 +594,10 @@ pub struct TokenContract {
     pub friends_amounts: [u64; MAX_FRIENDS_WALLETS],
     pub deployer_amount: u64,
     pub bump: u8,
-    pub badge_mint: Option<Pubkey>,
+    pub badge_mint: Option<Pubkey>,           // master badge mint, set by initialize_badge_edition
     pub badge_master_edition: Option<Pubkey>,
+    pub badge_editions_printed: u64,
+    pub soulbound_badges: bool,               // badges are Token-2022 NonTransferable mints
 }
 
 #[account]
@@ -156,12 +613,37 @@ pub struct UserSwapData {
 // EVENTS & ERRORS
 // ─────────────────────────────────────────────────────────────────────────────
 #[event] pub struct HandshakeEvent { pub child_program_id: Pubkey, pub meme_mint: Pubkey, pub deployer: Pubkey }
-#[event] pub struct BadgeMinted { pub user: Pubkey, pub badge_mint: Pubkey }
-#[event] pub struct GlobalTaxCollected { pub amount_in: u64, pub total_tax: u64, pub user: Pubkey, pub is_buy: bool }
-#[event] pub struct RewardsDistributed { pub swapper_sol: u64, pub badge_sol: u64 }
+#[event] pub struct BadgeMinted { pub user: Pubkey, pub badge_mint: Pubkey, pub soulbound: bool, pub tier: u8 }
+#[event] pub struct BadgeConfigUpdated { pub tiers: [BadgeTier; BADGE_TIER_COUNT], pub expiry_secs: i64 }
+#[event] pub struct BadgeRevoked { pub user: Pubkey, pub meme_mint: Pubkey, pub badge_mint: Pubkey, pub forfeited: u64, pub revoker: Pubkey }
+#[event] pub struct BadgeEditionInitialized { pub meme_mint: Pubkey, pub master_mint: Pubkey, pub master_edition: Pubkey }
+#[event] pub struct GlobalTaxCollected { pub amount_in: u64, pub total_tax: u64, pub user: Pubkey, pub is_buy: bool, pub meme_mint: Pubkey, pub split: TaxSplit }
+#[event] pub struct RewardsCranked { pub epoch: u64, pub meme_mint: Pubkey, pub keeper: Pubkey, pub processed: u64, pub cursor: u64, pub paid: u64, pub keeper_fee: u64 }
+#[event] pub struct KeeperFeeUpdated { pub keeper_fee_bps: u64 }
+#[event] pub struct RewardsDistributed { pub epoch: u64, pub swapper_sol: u64, pub badge_sol: u64, pub participants: u64 }
+#[event] pub struct SwapperRewardsClaimed { pub user: Pubkey, pub epoch: u64, pub amount: u64 }
+#[event] pub struct BadgeRewardsClaimed { pub user: Pubkey, pub badge_mint: Pubkey, pub amount: u64 }
 #[event] pub struct AirdropTriggered { pub meme_program_id: Pubkey, pub claimers: u64 }
-#[event] pub struct TreasuryWithdrawal { pub amount: u64, pub to: Pubkey }
-#[event] pub struct VaultRegistered { pub user: Pubkey, pub vault: Pubkey }
+#[event] pub struct VaultsInitialized { pub meme_mint: Pubkey, pub treasury_vault: Pubkey, pub lp_vault: Pubkey }
+#[event] pub struct WithdrawalQueued { pub id: u64, pub meme_mint: Pubkey, pub amount: u64, pub destination: Pubkey, pub executable_at: i64 }
+#[event] pub struct WithdrawalExecuted { pub id: u64, pub amount: u64, pub destination: Pubkey }
+#[event] pub struct WithdrawalCancelled { pub id: u64 }
+#[event] pub struct WithdrawalLimitsUpdated { pub withdrawal_delay: i64, pub epoch_withdrawal_cap: u64, pub activates_at: i64 }
+#[event] pub struct GuardianUpdated { pub guardian: Pubkey, pub activates_at: i64 }
+#[event] pub struct GuardianChangeCancelled { pub guardian: Pubkey }
+#[event] pub struct VaultRegistered { pub user: Pubkey, pub vault: Pubkey, pub auth_key: VaultAuthKey }
+#[event] pub struct ElGamalKeyRegistered { pub user: Pubkey, pub pubkey: [u8; 32] }
+#[event] pub struct VaultKeyRotated { pub user: Pubkey, pub vault: Pubkey, pub old_key: VaultAuthKey, pub new_key: VaultAuthKey }
+#[event] pub struct TaxScheduleUpdated { pub split: TaxSplit, pub activates_at: i64 }
+#[event] pub struct AdminProposed { pub current_admin: Pubkey, pub proposed_admin: Pubkey }
+#[event] pub struct AdminTransferred { pub old_admin: Pubkey, pub new_admin: Pubkey }
+#[event] pub struct AdminCouncilUpdated { pub council: Pubkey, pub members: Vec<Pubkey>, pub threshold: u8 }
+#[event] pub struct ChildProgramApproved { pub child_program_id: Pubkey }
+#[event] pub struct ChildProgramRevoked { pub child_program_id: Pubkey }
+#[event] pub struct CoinStatusChanged { pub meme_mint: Pubkey, pub status: CoinStatus }
+#[event] pub struct CoinBonded { pub meme_mint: Pubkey, pub raydium_pool: Pubkey }
+#[event] pub struct RegistryEntryMigrated { pub meme_mint: Pubkey, pub index: u64, pub skipped: bool }
+#[event] pub struct CoinTaxOverrideSet { pub meme_mint: Pubkey, pub tax_override: Option<TaxSplit>, pub activates_at: i64 }
 
 #[error_code]
 pub enum SafePumpError {
@@ -170,11 +652,11 @@ pub enum SafePumpError {
     #[msg("Invalid supply")] InvalidSupply,
     #[msg("Anti-sniper cooldown")] AntiSniperCooldown,
     #[msg("Math error")] MathError,
-    #[msg("Insufficient buy swaps")] InsufficientBuySwaps,
+    #[msg("Buy history below the lowest badge tier")] InsufficientBuySwaps,
     #[msg("Badge limit reached")] BadgeHolderLimitReached,
     #[msg("Meme coin already registered")] MemeCoinAlreadyRegistered,
     #[msg("Exceeds velocity limit")] ExceedsVelocityLimit,
-    #[msg("Invalid BLS signature")] InvalidBlsSignature,
+    #[msg("Invalid swap authorization signature")] InvalidSwapSignature,
     #[msg("Invalid nonce")] InvalidNonce,
     #[msg("Vault not registered")] VaultNotRegistered,
     #[msg("Distribution period not met")] DistributionPeriodNotMet,
@@ -186,17 +668,69 @@ pub enum SafePumpError {
     #[msg("Airdrop not triggered")] AirdropNotTriggered,
     #[msg("Mint must end with SPMP")] InvalidMintSuffix,
     #[msg("CPI rate limit")] CpiRateLimit,
+    #[msg("Tax buckets must sum to the total and stay under the cap")] InvalidTaxSchedule,
+    #[msg("Meme coin not registered")] MemeCoinNotRegistered,
+    #[msg("Legacy registry entry out of order")] RegistryMigrationMismatch,
+    #[msg("Meme coin is paused or delisted")] CoinNotActive,
+    #[msg("Meme coin is delisted")] CoinDelisted,
+    #[msg("Meme coin already bonded")] AlreadyBonded,
+    #[msg("Handshake not signed by the child program")] UnverifiedChildProgram,
+    #[msg("Mint authority is not the child's contract PDA")] InvalidMintAuthority,
+    #[msg("Child program not on the allow-list")] ChildProgramNotApproved,
+    #[msg("Child program already approved")] ChildProgramAlreadyApproved,
+    #[msg("Child program allow-list is full")] ChildProgramListFull,
+    #[msg("No admin transfer pending for this key")] NoPendingAdmin,
+    #[msg("Council members or threshold invalid")] InvalidCouncil,
+    #[msg("Not enough council signatures")] InsufficientCouncilSignatures,
+    #[msg("Withdrawal timelock not elapsed")] WithdrawalTimelocked,
+    #[msg("Withdrawal exceeds the per-epoch cap")] EpochWithdrawalCapExceeded,
+    #[msg("Withdrawal delay below minimum")] WithdrawalDelayTooShort,
+    #[msg("Missing reward recipient token account")] MissingRewardAccount,
+    #[msg("Reward token account not owned by recipient")] InvalidRewardAccount,
+    #[msg("Nothing to claim")] NothingToClaim,
+    #[msg("Rewards for this epoch already claimed")] RewardsAlreadyClaimed,
+    #[msg("Keeper fee above maximum")] KeeperFeeTooHigh,
+    #[msg("Crank batch size out of range")] InvalidCrankBatch,
+    #[msg("Crank pass already finished for this epoch")] CrankAlreadyComplete,
+    #[msg("Proof not verified by the ZK ElGamal proof program")] ZkProofProgramMismatch,
+    #[msg("Unexpected ZK proof type")] ZkProofTypeMismatch,
+    #[msg("Malformed ZK proof context")] InvalidZkProofContext,
+    #[msg("Caller does not hold this badge")] NotBadgeHolder,
+    #[msg("Legacy registry account is malformed")] InvalidLegacyRegistry,
+    #[msg("Legacy registry migration still in progress")] RegistryMigrationPending,
+    #[msg("Pool does not pair this coin with WSOL")] InvalidBondPool,
+    #[msg("ZK proof context belongs to another authority")] ZkProofAuthorityMismatch,
+    #[msg("Badge master edition not initialized")] BadgeEditionNotInitialized,
+    #[msg("Wrong badge mint path for this coin")] BadgeModeMismatch,
+    #[msg("Invalid badge tier configuration")] InvalidBadgeConfig,
+    #[msg("Badge holder is still active")] BadgeStillActive,
+    #[msg("Swap authorization has expired")] SwapAuthorizationExpired,
+    #[msg("Pool does not match the coin's Raydium pool")] PoolMismatch,
+    #[msg("Vault key proof of possession failed")] InvalidProofOfPossession,
+    #[msg("No guardian change pending")] NoPendingGuardian,
+    #[msg("Badge holder page does not cover this holder")] BadgeHolderPageMismatch,
+    #[msg("Not the earner's account for this badge")] InvalidBadgeAccount,
 }
 
 // ─────────────────────────────────────────────────────────────────────────────
-// BLS VERIFICATION
+// HELPERS
 // ─────────────────────────────────────────────────────────────────────────────
-fn verify_bls_sig(sig: [u8; 96], pk: [u8; 48], msg: &[u8]) -> bool {
-    let sig = match G2Projective::from_compressed(&sig) { CtOption::Some(s) => s, _ => return false };
-    let pk = match G1Affine::from_compressed(&pk) { CtOption::Some(p) => p, _ => return false };
-    let h = G2Projective::hash_to_curve(msg, b"SAFE-PUMP-V5", &[]).to_affine();
-    let g1 = G1Projective::generator().to_affine();
-    blstrs::pairing(&g1, &sig.to_affine()) == blstrs::pairing(&pk.into(), &h)
+/// WSOL transfer out of a vault whose token authority is the GlobalState PDA.
+fn transfer_from_vault<'info>(
+    token_program: &Program<'info, Token>,
+    global_state: &Account<'info, GlobalState>,
+    from: AccountInfo<'info>,
+    to: AccountInfo<'info>,
+    amount: u64,
+) -> Result<()> {
+    token::transfer(
+        CpiContext::new_with_signer(
+            token_program.to_account_info(),
+            Transfer { from, to, authority: global_state.to_account_info() },
+            &[&[b"global", &[global_state.bump]]],
+        ),
+        amount,
+    )
 }
 
 macro_rules! require_spmp_suffix {
@@ -206,6 +740,148 @@ macro_rules! require_spmp_suffix {
     }};
 }
 
+/// Appends `meme_mint` to the registry's current index page and returns its global index.
+/// The page may have just been created by `init_if_needed`.
+fn append_to_registry(index: &mut RegistryIndex, page: &AccountLoader<RegistryPage>, page_bump: u8, meme_mint: Pubkey) -> Result<u64> {
+    let mut page = match page.load_mut() {
+        Ok(page) => page,
+        Err(_) => page.load_init()?,
+    };
+    let position = index.entry_count;
+    let slot = (position % REGISTRY_PAGE_SIZE as u64) as usize;
+    page.page = index.current_page();
+    page.mints[slot] = meme_mint;
+    page.len = (slot + 1) as u16;
+    page.bump = page_bump;
+    index.entry_count = position.checked_add(1).ok_or(SafePumpError::MathError)?;
+    Ok(position)
+}
+
+// Legacy `MemeCoinRegistry` is read by offset: the whole Vec does not fit on the heap.
+// Layout: discriminator, u32 length, then (meme_mint, child_program_id) pairs.
+const LEGACY_REGISTRY_LEN_OFFSET: usize = 8;
+const LEGACY_REGISTRY_ENTRIES_OFFSET: usize = 8 + 4;
+
+/// Number of entries in the legacy registry; zero if it was never created.
+fn legacy_registry_len(legacy: &AccountInfo) -> Result<u64> {
+    if legacy.owner != &crate::ID {
+        return Ok(0);
+    }
+    let data = legacy.try_borrow_data()?;
+    require!(
+        data.len() >= LEGACY_REGISTRY_ENTRIES_OFFSET && data[..8] == *MemeCoinRegistry::DISCRIMINATOR,
+        SafePumpError::InvalidLegacyRegistry
+    );
+    let len = &data[LEGACY_REGISTRY_LEN_OFFSET..LEGACY_REGISTRY_ENTRIES_OFFSET];
+    Ok(u32::from_le_bytes([len[0], len[1], len[2], len[3]]) as u64)
+}
+
+/// `(meme_mint, child_program_id)` at `position` in the legacy registry.
+fn legacy_registry_entry(legacy: &AccountInfo, position: u64) -> Result<(Pubkey, Pubkey)> {
+    require!(position < legacy_registry_len(legacy)?, SafePumpError::RegistryMigrationMismatch);
+    let data = legacy.try_borrow_data()?;
+    let at = LEGACY_REGISTRY_ENTRIES_OFFSET + position as usize * 64;
+    let pair = data.get(at..at + 64).ok_or(SafePumpError::InvalidLegacyRegistry)?;
+    let key = |bytes: &[u8]| Pubkey::try_from(bytes).map_err(|_| error!(SafePumpError::InvalidLegacyRegistry));
+    Ok((key(&pair[..32])?, key(&pair[32..])?))
+}
+
+/// Loads the holder page `page`, initializing it if `init_if_needed` just created it.
+fn load_holder_page<'a>(loader: &'a AccountLoader<BadgeHolderPage>, meme_mint: Pubkey, page: u64, bump: u8) -> Result<RefMut<'a, BadgeHolderPage>> {
+    if let Ok(loaded) = loader.load_mut() {
+        return Ok(loaded);
+    }
+    let mut loaded = loader.load_init()?;
+    loaded.meme_mint = meme_mint;
+    loaded.page = page;
+    loaded.bump = bump;
+    Ok(loaded)
+}
+
+/// The loader among `pages` that covers holder `index`. Callers pin each page to the
+/// coin with seeds, so the stored page number is enough to tell them apart.
+fn page_for<'a, 'info>(pages: &[&'a AccountLoader<'info, BadgeHolderPage>], index: u64) -> Result<&'a AccountLoader<'info, BadgeHolderPage>> {
+    let wanted = index / BADGE_HOLDER_PAGE_SIZE as u64;
+    for page in pages {
+        if page.load()?.page == wanted {
+            return Ok(page);
+        }
+    }
+    err!(SafePumpError::BadgeHolderPageMismatch)
+}
+
+fn holder_at(pages: &[&AccountLoader<BadgeHolderPage>], index: u64) -> Result<Pubkey> {
+    let page = page_for(pages, index)?.load()?;
+    Ok(page.holders[(index % BADGE_HOLDER_PAGE_SIZE as u64) as usize])
+}
+
+// Pages may be passed more than once, so each access loads and drops its own borrow
+fn set_holder_at(pages: &[&AccountLoader<BadgeHolderPage>], index: u64, holder: Pubkey) -> Result<()> {
+    let mut page = page_for(pages, index)?.load_mut()?;
+    page.holders[(index % BADGE_HOLDER_PAGE_SIZE as u64) as usize] = holder;
+    Ok(())
+}
+
+/// Shared tail of both badge mint paths: eligibility, holder slot and reward record.
+/// `page` must be the holder page `holders.next_page()`.
+fn register_badge(
+    config: &BadgeConfig,
+    buy_counter: &BuyCounter,
+    holders: &mut BadgeHolders,
+    page: &mut BadgeHolderPage,
+    record: &mut BadgeRecord,
+    badge_mint: Pubkey,
+    meme_mint: Pubkey,
+    user: Pubkey,
+    soulbound: bool,
+    bump: u8,
+) -> Result<()> {
+    let tier = config.tier_for(buy_counter).ok_or(SafePumpError::InsufficientBuySwaps)?;
+    let weight = config.tiers[tier as usize].weight;
+    require!(holders.holder_count < MAX_BADGE_HOLDERS as u64, SafePumpError::BadgeHolderLimitReached);
+    page.holders[(holders.holder_count % BADGE_HOLDER_PAGE_SIZE as u64) as usize] = user;
+    holders.holder_count += 1;
+
+    record.badge_mint = badge_mint;
+    record.meme_mint = meme_mint;
+    record.owner = user;
+    record.reward_debt = holders.acc_reward_per_badge();
+    record.claimed = 0;
+    record.soulbound = soulbound;
+    record.tier = tier;
+    record.weight = weight;
+    record.bump = bump;
+    holders.total_badge_weight = holders.total_badge_weight.checked_add(weight).ok_or(SafePumpError::MathError)?;
+    emit!(BadgeMinted { user, badge_mint, soulbound, tier });
+    Ok(())
+}
+
+/// PDA only `child_program_id` can sign for — proves a CPI really came from that program.
+fn child_authority_of(child_program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[CHILD_AUTHORITY_SEED], child_program_id).0
+}
+
+/// `authority` must be `expected` and either sign itself, or be an AdminCouncil
+/// PDA with at least `threshold` of its members signing in `remaining`.
+fn require_authority(expected: Pubkey, authority: &AccountInfo, remaining: &[AccountInfo]) -> Result<()> {
+    require_keys_eq!(authority.key(), expected, SafePumpError::Unauthorized);
+    if authority.is_signer {
+        return Ok(());
+    }
+
+    require_keys_eq!(*authority.owner, crate::id(), SafePumpError::Unauthorized);
+    let council = AdminCouncil::try_deserialize(&mut &authority.try_borrow_data()?[..])?;
+    let approvals = council.members.iter()
+        .filter(|m| remaining.iter().any(|a| a.is_signer && a.key() == **m))
+        .count();
+    require!(approvals >= council.threshold as usize, SafePumpError::InsufficientCouncilSignatures);
+    Ok(())
+}
+
+fn require_admin(state: &GlobalState, authority: &AccountInfo, remaining: &[AccountInfo]) -> Result<()> {
+    require_authority(state.admin, authority, remaining)
+}
+
 // ─────────────────────────────────────────────────────────────────────────────
 // PROGRAM
 // ─────────────────────────────────────────────────────────────────────────────
@@ -213,33 +889,374 @@ macro_rules! require_spmp_suffix {
 pub mod safe_pump {
     use super::*;
 
-    pub fn initialize_global(ctx: Context<InitializeGlobal>, treasury_wallet: Pubkey) -> Result<()> {
+    pub fn initialize_global(ctx: Context<InitializeGlobal>, treasury_wallet: Pubkey, admin: Pubkey) -> Result<()> {
         let state = &mut ctx.accounts.global_state;
         require!(!state.is_initialized, SafePumpError::AlreadyInitialized);
         state.is_initialized = true;
         state.treasury_wallet = treasury_wallet;
+        state.admin = admin;
+        state.pending_admin = None;
         state.bond_timestamp = Clock::get()?.unix_timestamp;
+        state.approved_child_programs = Vec::new();
+        state.withdrawal_delay = DEFAULT_WITHDRAWAL_DELAY;
+        state.epoch_withdrawal_cap = DEFAULT_EPOCH_WITHDRAWAL_CAP;
+        state.withdrawal_epoch = 0;
+        state.withdrawn_this_epoch = 0;
+        state.next_withdrawal_id = 0;
+        state.keeper_fee_bps = DEFAULT_KEEPER_FEE_BPS;
+        state.pending_withdrawal_limits = None;
+        state.withdrawal_limits_activate_at = 0;
+        state.guardian = Pubkey::default();
+        state.pending_guardian = None;
+        state.guardian_activates_at = 0;
         state.bump = ctx.bumps.global_state;
         Ok(())
     }
 
+    /// Upgrades a GlobalState created before the admin, allow-list and withdrawal
+    /// fields existed. Those fields follow the original ones, so the old bytes read
+    /// back unchanged once the account is zero-extended.
+    pub fn migrate_global_state(ctx: Context<MigrateGlobalState>, admin: Pubkey) -> Result<()> {
+        let info = ctx.accounts.global_state.to_account_info();
+        require!(info.data_len() == LEGACY_GLOBAL_STATE_LEN, SafePumpError::AlreadyInitialized);
+
+        let shortfall = Rent::get()?.minimum_balance(GLOBAL_STATE_LEN).saturating_sub(info.lamports());
+        if shortfall > 0 {
+            system_program::transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    system_program::Transfer { from: ctx.accounts.authority.to_account_info(), to: info.clone() },
+                ),
+                shortfall,
+            )?;
+        }
+        info.resize(GLOBAL_STATE_LEN)?;
+
+        let mut state = GlobalState::try_deserialize(&mut &info.try_borrow_data()?[..])?;
+        state.admin = admin;
+        state.pending_admin = None;
+        state.withdrawal_delay = DEFAULT_WITHDRAWAL_DELAY;
+        state.epoch_withdrawal_cap = DEFAULT_EPOCH_WITHDRAWAL_CAP;
+        state.keeper_fee_bps = DEFAULT_KEEPER_FEE_BPS;
+        state.try_serialize(&mut &mut info.try_borrow_mut_data()?[..])?;
+        Ok(())
+    }
+
+    /// First step of an admin handover; the proposed key must call `accept_admin`.
+    pub fn propose_admin(ctx: Context<UpdateAdmin>, proposed_admin: Pubkey) -> Result<()> {
+        require_admin(&ctx.accounts.global_state, &ctx.accounts.authority, ctx.remaining_accounts)?;
+        let state = &mut ctx.accounts.global_state;
+        state.pending_admin = Some(proposed_admin);
+        emit!(AdminProposed { current_admin: state.admin, proposed_admin });
+        Ok(())
+    }
+
+    pub fn accept_admin(ctx: Context<UpdateAdmin>) -> Result<()> {
+        let proposed = ctx.accounts.global_state.pending_admin.ok_or(SafePumpError::NoPendingAdmin)?;
+        require_authority(proposed, &ctx.accounts.authority, ctx.remaining_accounts)?;
+        let state = &mut ctx.accounts.global_state;
+        let old_admin = state.admin;
+        state.admin = proposed;
+        state.pending_admin = None;
+        emit!(AdminTransferred { old_admin, new_admin: proposed });
+        Ok(())
+    }
+
+    /// Creates the council PDA. Hand it admin rights with propose_admin/accept_admin.
+    pub fn initialize_admin_council(ctx: Context<InitializeAdminCouncil>, members: Vec<Pubkey>, threshold: u8) -> Result<()> {
+        require_admin(&ctx.accounts.global_state, &ctx.accounts.authority, ctx.remaining_accounts)?;
+        AdminCouncil::validate(&members, threshold)?;
+        let council = &mut ctx.accounts.admin_council;
+        council.members = members.clone();
+        council.threshold = threshold;
+        council.bump = ctx.bumps.admin_council;
+        emit!(AdminCouncilUpdated { council: council.key(), members, threshold });
+        Ok(())
+    }
+
+    /// Changes membership; must be approved by the council's current threshold.
+    pub fn update_admin_council(ctx: Context<UpdateAdminCouncil>, members: Vec<Pubkey>, threshold: u8) -> Result<()> {
+        let council_info = ctx.accounts.admin_council.to_account_info();
+        require_authority(council_info.key(), &council_info, ctx.remaining_accounts)?;
+        AdminCouncil::validate(&members, threshold)?;
+        let council = &mut ctx.accounts.admin_council;
+        council.members = members.clone();
+        council.threshold = threshold;
+        emit!(AdminCouncilUpdated { council: council.key(), members, threshold });
+        Ok(())
+    }
+
+    pub fn approve_child_program(ctx: Context<UpdateChildPrograms>, child_program_id: Pubkey) -> Result<()> {
+        require_admin(&ctx.accounts.global_state, &ctx.accounts.authority, ctx.remaining_accounts)?;
+        let state = &mut ctx.accounts.global_state;
+        require!(!state.is_child_approved(&child_program_id), SafePumpError::ChildProgramAlreadyApproved);
+        require!(state.approved_child_programs.len() < MAX_APPROVED_CHILD_PROGRAMS, SafePumpError::ChildProgramListFull);
+        state.approved_child_programs.push(child_program_id);
+        emit!(ChildProgramApproved { child_program_id });
+        Ok(())
+    }
+
+    pub fn revoke_child_program(ctx: Context<UpdateChildPrograms>, child_program_id: Pubkey) -> Result<()> {
+        require_admin(&ctx.accounts.global_state, &ctx.accounts.authority, ctx.remaining_accounts)?;
+        let state = &mut ctx.accounts.global_state;
+        let pos = state.approved_child_programs.iter()
+            .position(|p| *p == child_program_id)
+            .ok_or(SafePumpError::ChildProgramNotApproved)?;
+        state.approved_child_programs.swap_remove(pos);
+        emit!(ChildProgramRevoked { child_program_id });
+        Ok(())
+    }
+
+    pub fn initialize_rewards(ctx: Context<InitializeRewards>) -> Result<()> {
+        require_admin(&ctx.accounts.global_state, &ctx.accounts.authority, ctx.remaining_accounts)?;
+        let rewards = &mut ctx.accounts.rewards;
+        rewards.last_distribution_timestamp = Clock::get()?.unix_timestamp;
+        rewards.current_epoch = 0;
+        rewards.bump = ctx.bumps.rewards;
+        Ok(())
+    }
+
+    pub fn initialize_tax_schedule(ctx: Context<InitializeTaxSchedule>) -> Result<()> {
+        require_admin(&ctx.accounts.global_state, &ctx.accounts.authority, ctx.remaining_accounts)?;
+        let schedule = &mut ctx.accounts.tax_schedule;
+        schedule.active = TaxSplit {
+            total_bps: GLOBAL_TAX_BPS,
+            lp_bps: GLOBAL_LP_TAX_BPS,
+            swapper_reward_bps: SWAPPER_REWARD_TAX_BPS,
+            badge_reward_bps: BADGE_REWARD_TAX_BPS,
+            treasury_bps: TREASURY_TAX_BPS,
+        };
+        schedule.pending = None;
+        schedule.activates_at = 0;
+        schedule.bump = ctx.bumps.tax_schedule;
+        Ok(())
+    }
+
+    pub fn update_tax_schedule(ctx: Context<UpdateTaxSchedule>, split: TaxSplit) -> Result<()> {
+        require_admin(&ctx.accounts.global_state, &ctx.accounts.authority, ctx.remaining_accounts)?;
+        require!(split.is_valid(), SafePumpError::InvalidTaxSchedule);
+
+        let now = Clock::get()?.unix_timestamp;
+        let schedule = &mut ctx.accounts.tax_schedule;
+        // Promote a matured pending split before queueing the next one
+        schedule.active = schedule.effective(now);
+        schedule.pending = Some(split);
+        schedule.activates_at = now.checked_add(TAX_SCHEDULE_TIMELOCK).ok_or(SafePumpError::MathError)?;
+        emit!(TaxScheduleUpdated { split, activates_at: schedule.activates_at });
+        Ok(())
+    }
+
+    pub fn initialize_badge_config(ctx: Context<InitializeBadgeConfig>) -> Result<()> {
+        require_admin(&ctx.accounts.global_state, &ctx.accounts.authority, ctx.remaining_accounts)?;
+        let config = &mut ctx.accounts.badge_config;
+        config.tiers = DEFAULT_BADGE_TIERS;
+        config.expiry_secs = DEFAULT_BADGE_EXPIRY;
+        config.bump = ctx.bumps.badge_config;
+        Ok(())
+    }
+
+    pub fn update_badge_config(ctx: Context<UpdateBadgeConfig>, tiers: [BadgeTier; BADGE_TIER_COUNT], expiry_secs: i64) -> Result<()> {
+        require_admin(&ctx.accounts.global_state, &ctx.accounts.authority, ctx.remaining_accounts)?;
+        require!(BadgeConfig::is_valid(&tiers) && expiry_secs >= 0, SafePumpError::InvalidBadgeConfig);
+        ctx.accounts.badge_config.tiers = tiers;
+        ctx.accounts.badge_config.expiry_secs = expiry_secs;
+        emit!(BadgeConfigUpdated { tiers, expiry_secs });
+        Ok(())
+    }
+
+    pub fn set_coin_tax_override(ctx: Context<SetCoinTaxOverride>, tax_override: Option<TaxSplit>) -> Result<()> {
+        require_admin(&ctx.accounts.global_state, &ctx.accounts.authority, ctx.remaining_accounts)?;
+        if let Some(split) = tax_override {
+            require!(split.is_valid() && split.total_bps <= MAX_COIN_TAX_BPS, SafePumpError::InvalidTaxSchedule);
+        }
+
+        // Same 48h notice as a global schedule change, including clearing an override
+        let now = Clock::get()?.unix_timestamp;
+        let entry = &mut ctx.accounts.registry_entry;
+        entry.tax_override = entry.effective_tax_override(now);
+        entry.pending_tax_override = Some(tax_override);
+        entry.tax_override_activates_at = now.checked_add(TAX_SCHEDULE_TIMELOCK).ok_or(SafePumpError::MathError)?;
+        emit!(CoinTaxOverrideSet { meme_mint: entry.meme_mint, tax_override, activates_at: entry.tax_override_activates_at });
+        Ok(())
+    }
+
     pub fn handshake(ctx: Context<Handshake>, child_program_id: Pubkey) -> Result<()> {
         require_spmp_suffix!(ctx.accounts.meme_mint);
-        let registry = &mut ctx.accounts.registry;
-        let entry = (ctx.accounts.meme_mint.key(), child_program_id);
-        require!(!registry.entries.contains(&entry), SafePumpError::MemeCoinAlreadyRegistered);
-        registry.entries.push(entry);
+        require!(ctx.accounts.global_state.is_child_approved(&child_program_id), SafePumpError::ChildProgramNotApproved);
+        require_keys_eq!(ctx.accounts.child_authority.key(), child_authority_of(&child_program_id), SafePumpError::UnverifiedChildProgram);
+        let (expected_contract, _) = Pubkey::find_program_address(&[CONTRACT_SEED, ctx.accounts.deployer.key().as_ref()], &child_program_id);
+        require!(ctx.accounts.meme_mint.mint_authority == COption::Some(expected_contract), SafePumpError::InvalidMintAuthority);
+        let index = &mut ctx.accounts.registry_index;
+        // Until every legacy coin has its entry, a legacy mint could be registered a second time
+        require!(index.migrated_count >= legacy_registry_len(&ctx.accounts.legacy_registry)?, SafePumpError::RegistryMigrationPending);
+        index.bump = ctx.bumps.registry_index;
+        let position = append_to_registry(index, &ctx.accounts.registry_page, ctx.bumps.registry_page, ctx.accounts.meme_mint.key())?;
+
+        let clock = Clock::get()?;
+        let entry = &mut ctx.accounts.registry_entry;
+        entry.meme_mint = ctx.accounts.meme_mint.key();
+        entry.child_program_id = child_program_id;
+        entry.tax_override = None;
+        entry.index = position;
+        entry.deployer = ctx.accounts.deployer.key();
+        entry.registered_slot = clock.slot;
+        entry.registered_at = clock.unix_timestamp;
+        entry.bonded = false;
+        entry.raydium_pool = Pubkey::default();
+        entry.status = CoinStatus::Active;
+        entry.pending_tax_override = None;
+        entry.tax_override_activates_at = 0;
+        entry.bump = ctx.bumps.registry_entry;
+
+        let mut holders = ctx.accounts.badge_holders.load_init()?;
+        holders.meme_mint = ctx.accounts.meme_mint.key();
+        holders.bump = ctx.bumps.badge_holders;
+        let mut page = ctx.accounts.badge_holder_page.load_init()?;
+        page.meme_mint = ctx.accounts.meme_mint.key();
+        page.bump = ctx.bumps.badge_holder_page;
         emit!(HandshakeEvent { child_program_id, meme_mint: ctx.accounts.meme_mint.key(), deployer: ctx.accounts.deployer.key() });
         Ok(())
     }
 
-    pub fn register_vault(ctx: Context<RegisterVault>) -> Result<()> {
+    /// Moves the next entry of the legacy `MemeCoinRegistry` into the sharded layout.
+    /// A mint that already has an entry (the legacy registry only deduplicated
+    /// (mint, child) pairs) is skipped rather than wedging the migration. Legacy coins
+    /// predate per-coin badge lists, so their `BadgeHolders` and first holder page are created here.
+    pub fn migrate_registry_entry(ctx: Context<MigrateRegistryEntry>, meme_mint: Pubkey) -> Result<()> {
+        require_admin(&ctx.accounts.global_state, &ctx.accounts.authority, ctx.remaining_accounts)?;
+        let index = &mut ctx.accounts.registry_index;
+        let (legacy_mint, legacy_child) = legacy_registry_entry(&ctx.accounts.legacy_registry, index.migrated_count)?;
+        require_keys_eq!(legacy_mint, meme_mint, SafePumpError::RegistryMigrationMismatch);
+        index.bump = ctx.bumps.registry_index;
+        index.migrated_count += 1;
+
+        if ctx.accounts.badge_holders.load_mut().is_err() {
+            let mut holders = ctx.accounts.badge_holders.load_init()?;
+            holders.meme_mint = meme_mint;
+            holders.bump = ctx.bumps.badge_holders;
+        }
+        load_holder_page(&ctx.accounts.badge_holder_page, meme_mint, 0, ctx.bumps.badge_holder_page)?;
+
+        let entry = &mut ctx.accounts.registry_entry;
+        if entry.meme_mint == meme_mint {
+            emit!(RegistryEntryMigrated { meme_mint, index: entry.index, skipped: true });
+            return Ok(());
+        }
+        let position = append_to_registry(index, &ctx.accounts.registry_page, ctx.bumps.registry_page, meme_mint)?;
+
+        // Legacy entries never recorded their deployer or launch time
+        let clock = Clock::get()?;
+        entry.meme_mint = meme_mint;
+        entry.child_program_id = legacy_child;
+        entry.tax_override = None;
+        entry.index = position;
+        entry.deployer = Pubkey::default();
+        entry.registered_slot = clock.slot;
+        entry.registered_at = clock.unix_timestamp;
+        entry.bonded = false;
+        entry.raydium_pool = Pubkey::default();
+        entry.status = CoinStatus::Active;
+        entry.pending_tax_override = None;
+        entry.tax_override_activates_at = 0;
+        entry.bump = ctx.bumps.registry_entry;
+        emit!(RegistryEntryMigrated { meme_mint, index: position, skipped: false });
+        Ok(())
+    }
+
+    pub fn set_coin_status(ctx: Context<SetCoinStatus>, status: CoinStatus) -> Result<()> {
+        require_admin(&ctx.accounts.global_state, &ctx.accounts.authority, ctx.remaining_accounts)?;
+        let entry = &mut ctx.accounts.registry_entry;
+        require!(entry.status != CoinStatus::Delisted, SafePumpError::CoinDelisted);
+        entry.status = status;
+        emit!(CoinStatusChanged { meme_mint: entry.meme_mint, status });
+        Ok(())
+    }
+
+    pub fn record_bond(ctx: Context<RecordBond>) -> Result<()> {
+        let authority = &ctx.accounts.authority;
+        let entry = &ctx.accounts.registry_entry;
+        let is_launcher = authority.is_signer
+            && (authority.key() == entry.deployer || authority.key() == child_authority_of(&entry.child_program_id));
+        if !is_launcher {
+            require_admin(&ctx.accounts.global_state, authority, ctx.remaining_accounts)?;
+        }
+
+        // The pool must trade this coin against WSOL, in either token order
+        let pool = ctx.accounts.pool_state.load()?;
+        let (token_0, token_1) = (pool.token_0_mint, pool.token_1_mint);
+        let meme_mint = ctx.accounts.registry_entry.meme_mint;
+        require!(
+            (token_0 == meme_mint && token_1 == native_mint::ID) || (token_1 == meme_mint && token_0 == native_mint::ID),
+            SafePumpError::InvalidBondPool
+        );
+        drop(pool);
+
+        let entry = &mut ctx.accounts.registry_entry;
+        require!(!entry.bonded, SafePumpError::AlreadyBonded);
+        entry.bonded = true;
+        entry.raydium_pool = ctx.accounts.pool_state.key();
+        emit!(CoinBonded { meme_mint: entry.meme_mint, raydium_pool: entry.raydium_pool });
+        Ok(())
+    }
+
+    /// `pop_sig` is the key's signature over `vault_key_pop_message(user, auth_key)`
+    /// (empty for Ed25519 keys, whose signature rides in a precompile instruction).
+    pub fn register_vault(ctx: Context<RegisterVault>, auth_key: VaultAuthKey, pop_sig: Vec<u8>) -> Result<()> {
+        let user = ctx.accounts.user.key();
+        require!(
+            auth_key.verify(&ctx.accounts.instructions, &vault_key_pop_message(&user, &auth_key), VAULT_KEY_POP_DST, &pop_sig),
+            SafePumpError::InvalidProofOfPossession
+        );
+
         let vault = &mut ctx.accounts.vault;
         vault.bump = ctx.bumps.vault;
         vault.nonce = 0;
         vault.last_signer = Pubkey::default();
+        vault.auth_key = auth_key;
         ctx.accounts.user_state.vault = ctx.accounts.vault.key();
-        emit!(VaultRegistered { user: ctx.accounts.user.key(), vault: ctx.accounts.vault.key() });
+        emit!(VaultRegistered { user, vault: ctx.accounts.vault.key(), auth_key });
+        Ok(())
+    }
+
+    /// Sets the ElGamal key confidential payouts to the user are encrypted to. The
+    /// PubkeyValidity proof, verified by the ZK ElGamal proof program, shows the user
+    /// holds its secret key; re-registering replaces it.
+    pub fn register_elgamal_key(ctx: Context<RegisterElGamalKey>, proof_location: ProofLocation) -> Result<()> {
+        let user = ctx.accounts.user.key();
+        let context = zk_proof::verify_and_extract_context::<PubkeyValidityProofData, PubkeyValidityProofContext>(
+            proof_location,
+            &ctx.accounts.proof_account,
+            &user,
+        )?;
+
+        let key = &mut ctx.accounts.elgamal_key;
+        key.user = user;
+        key.pubkey.copy_from_slice(bytemuck::bytes_of(&context.pubkey));
+        key.bump = ctx.bumps.elgamal_key;
+        emit!(ElGamalKeyRegistered { user, pubkey: key.pubkey });
+        Ok(())
+    }
+
+    /// Replaces the vault's key, possibly with one of another scheme. The old key
+    /// consents over the current nonce, the new key proves possession; the nonce
+    /// advances so neither can be replayed.
+    pub fn rotate_vault_key(ctx: Context<RotateVaultKey>, new_key: VaultAuthKey, old_key_sig: Vec<u8>, new_key_pop_sig: Vec<u8>) -> Result<()> {
+        let user = ctx.accounts.user.key();
+        let instructions = &ctx.accounts.instructions;
+        let vault = &mut ctx.accounts.vault;
+        require!(
+            vault.auth_key.verify(instructions, &vault_key_rotation_message(&user, vault.nonce, &new_key), VAULT_KEY_ROTATION_DST, &old_key_sig),
+            SafePumpError::InvalidSwapSignature
+        );
+        require!(
+            new_key.verify(instructions, &vault_key_pop_message(&user, &new_key), VAULT_KEY_POP_DST, &new_key_pop_sig),
+            SafePumpError::InvalidProofOfPossession
+        );
+
+        let old_key = vault.auth_key;
+        vault.auth_key = new_key;
+        vault.nonce = vault.nonce.checked_add(1).ok_or(SafePumpError::MathError)?;
+        emit!(VaultKeyRotated { user, vault: vault.key(), old_key, new_key });
         Ok(())
     }
 
@@ -248,14 +1265,15 @@ pub mod safe_pump {
         amount_in: u64,
         is_buy: bool,
         minimum_amount_out: u64,
-        bls_sig: [u8; 96],
-        bls_pk: [u8; 48],
+        auth_sig: Vec<u8>,
         nonce: u64,
+        expiry_slot: u64,
     ) -> Result<()> {
         require_spmp_suffix!(ctx.accounts.mint);
 
         let contract = &ctx.accounts.contract;
         require!(contract.is_initialized, SafePumpError::NotInitialized);
+        require!(ctx.accounts.registry_entry.status == CoinStatus::Active, SafePumpError::CoinNotActive);
 
         let clock = Clock::get()?;
 
@@ -270,38 +1288,45 @@ pub mod safe_pump {
             SafePumpError::SellCooldownNotMet
         );
 
-        // ZK Vault + BLS verification
+        // ZK Vault; the signature over the swap authorization is checked once, by the
+        // global_tax_swap CPI below
         require_keys_eq!(ctx.accounts.vault.key(), ctx.accounts.user_state.vault, SafePumpError::VaultNotRegistered);
         require!(ctx.accounts.vault.nonce == nonce, SafePumpError::InvalidNonce);
 
-        let mut msg = Vec::new();
-        msg.extend_from_slice(&amount_in.to_le_bytes());
-        msg.push(if is_buy { 1 } else { 0 });
-        msg.extend_from_slice(&minimum_amount_out.to_le_bytes());
-        msg.extend_from_slice(&nonce.to_le_bytes());
-        msg.extend_from_slice(ctx.accounts.user.key().as_ref());
-        require!(verify_bls_sig(bls_sig, bls_pk, &msg), SafePumpError::InvalidBlsSignature);
-
         // === FIRST: Collect global tax via Mothership CPI ===
         let mothership_cpi_accounts = crate::cpi::accounts::GlobalTaxSwap {
             global_state: ctx.accounts.global_state.to_account_info(),
             user: ctx.accounts.user.to_account_info(),
             user_sol: ctx.accounts.user_sol.to_account_info(),
+            meme_mint: ctx.accounts.mint.to_account_info(),
+            registry_entry: ctx.accounts.registry_entry.to_account_info(),
             vault: ctx.accounts.vault.to_account_info(),
             expected_vault: ctx.accounts.expected_vault.to_account_info(),
             lp_vault: ctx.accounts.lp_vault.to_account_info(),
             treasury_vault: ctx.accounts.treasury_vault.to_account_info(),
+            rewards_vault: ctx.accounts.rewards_vault.to_account_info(),
             rewards: ctx.accounts.rewards.to_account_info(),
+            reward_accrual: ctx.accounts.reward_accrual.to_account_info(),
+            buy_counter: ctx.accounts.buy_counter.to_account_info(),
             badge_holders: ctx.accounts.badge_holders.to_account_info(),
+            tax_schedule: ctx.accounts.tax_schedule.to_account_info(),
             velocity: ctx.accounts.velocity.to_account_info(),
+            instructions: ctx.accounts.instructions.to_account_info(),
             token_program: ctx.accounts.token_program.to_account_info(),
             system_program: ctx.accounts.system_program.to_account_info(),
         };
         let mothership_cpi_ctx = CpiContext::new(ctx.accounts.mothership_program.to_account_info(), mothership_cpi_accounts);
-        crate::cpi::global_tax_swap(mothership_cpi_ctx, amount_in, is_buy, minimum_amount_out, bls_sig, bls_pk, nonce)?;
+        crate::cpi::global_tax_swap(mothership_cpi_ctx, amount_in, is_buy, minimum_amount_out, auth_sig, nonce, ctx.accounts.pool_state.key(), expiry_slot)?;
+        // The CPI wrote these; reload so this instruction's exit doesn't write the
+        // pre-CPI copies back over them
+        ctx.accounts.global_state.reload()?;
+        ctx.accounts.rewards.reload()?;
+        ctx.accounts.vault.reload()?;
 
         // === THEN: Perform actual Raydium swap (after tax) ===
-        let net_amount = amount_in * (10_000 - GLOBAL_TAX_BPS) / 10_000;
+        let net_amount = ctx.accounts.registry_entry
+            .tax_split(&ctx.accounts.tax_schedule, clock.unix_timestamp)
+            .net_amount(amount_in)?;
 
         if is_buy {
             raydium_cp_swap::cpi::swap_base_in(
@@ -343,9 +1368,8 @@ pub mod safe_pump {
             )?;
         }
 
-        // Update user state
+        // Update user state; global_tax_swap already advanced the vault nonce
         ctx.accounts.user_state.last_swap_timestamp = clock.unix_timestamp;
-        ctx.accounts.vault.nonce = nonce.checked_add(1).ok_or(SafePumpError::MathError)?;
 
         Ok(())
     }
@@ -355,12 +1379,15 @@ pub mod safe_pump {
         amount_in: u64,
         is_buy: bool,
         min_out: u64,
-        bls_sig: [u8; 96],
-        bls_pk: [u8; 48],
+        auth_sig: Vec<u8>,
         nonce: u64,
+        pool: Pubkey,
+        expiry_slot: u64,
     ) -> Result<()> {
         let state = &mut ctx.accounts.global_state;
         require!(state.is_initialized, SafePumpError::NotInitialized);
+        require!(ctx.accounts.registry_entry.status == CoinStatus::Active, SafePumpError::CoinNotActive);
+        require!(state.is_child_approved(&ctx.accounts.registry_entry.child_program_id), SafePumpError::ChildProgramNotApproved);
 
         let clock = Clock::get()?;
         if state.swap_count == 0 {
@@ -370,13 +1397,25 @@ pub mod safe_pump {
         require_keys_eq!(ctx.accounts.vault.key(), ctx.accounts.expected_vault.key(), SafePumpError::VaultNotRegistered);
         require!(ctx.accounts.vault.nonce == nonce, SafePumpError::InvalidNonce);
 
-        let mut msg = Vec::new();
-        msg.extend_from_slice(&amount_in.to_le_bytes());
-        msg.push(if is_buy { 1 } else { 0 });
-        msg.extend_from_slice(&min_out.to_le_bytes());
-        msg.extend_from_slice(&nonce.to_le_bytes());
-        msg.extend_from_slice(ctx.accounts.user.key().as_ref());
-        require!(verify_bls_sig(bls_sig, bls_pk, &msg), SafePumpError::InvalidBlsSignature);
+        // Once bonded the pool is known; before that it's the pool the child will create
+        let entry = &ctx.accounts.registry_entry;
+        require!(!entry.bonded || pool == entry.raydium_pool, SafePumpError::PoolMismatch);
+        let auth = SwapAuthorization {
+            program_id: entry.child_program_id,
+            mint: ctx.accounts.meme_mint.key(),
+            pool,
+            user: ctx.accounts.user.key(),
+            is_buy,
+            amount_in,
+            min_out,
+            nonce,
+            expiry_slot,
+        };
+        require!(!auth.is_expired(clock.slot), SafePumpError::SwapAuthorizationExpired);
+        require!(
+            ctx.accounts.vault.auth_key.verify(&ctx.accounts.instructions, &auth.encode(), SWAP_AUTHORIZATION_DST, &auth_sig),
+            SafePumpError::InvalidSwapSignature
+        );
 
         let velocity = &mut ctx.accounts.velocity;
         if velocity.block_slot != clock.slot {
@@ -390,88 +1429,512 @@ pub mod safe_pump {
             require!(velocity.total_bought_sol <= limit, SafePumpError::ExceedsVelocityLimit);
         }
 
-        let total_tax = amount_in * GLOBAL_TAX_BPS / 10_000;
-        let lp_tax = total_tax * GLOBAL_LP_TAX_BPS / GLOBAL_TAX_BPS;
-        let swapper_tax = total_tax * SWAPPER_REWARD_TAX_BPS / GLOBAL_TAX_BPS;
-        let badge_tax = total_tax * BADGE_REWARD_TAX_BPS / GLOBAL_TAX_BPS;
-        let treasury_tax = total_tax * TREASURY_TAX_BPS / GLOBAL_TAX_BPS;
+        let meme_mint = ctx.accounts.meme_mint.key();
+        let split = ctx.accounts.registry_entry.tax_split(&ctx.accounts.tax_schedule, clock.unix_timestamp);
+        let total_tax = split.total_tax(amount_in)?;
+        let (lp_tax, swapper_tax, mut badge_tax, mut treasury_tax) = split.shares(total_tax)?;
+        // No live badge to earn the badge share: send it to treasury instead of letting
+        // it pile up for whoever mints first
+        let mut holders = ctx.accounts.badge_holders.load_mut()?;
+        if holders.total_badge_weight == 0 {
+            treasury_tax = treasury_tax.checked_add(badge_tax).ok_or(SafePumpError::MathError)?;
+            badge_tax = 0;
+        }
 
         if lp_tax > 0 { token::transfer(CpiContext::new(ctx.accounts.token_program.to_account_info(), Transfer { from: ctx.accounts.user_sol.to_account_info(), to: ctx.accounts.lp_vault.to_account_info(), authority: ctx.accounts.user.to_account_info() }), lp_tax)?; }
         if treasury_tax > 0 { token::transfer(CpiContext::new(ctx.accounts.token_program.to_account_info(), Transfer { from: ctx.accounts.user_sol.to_account_info(), to: ctx.accounts.treasury_vault.to_account_info(), authority: ctx.accounts.user.to_account_info() }), treasury_tax)?; }
+        let reward_tax = swapper_tax.checked_add(badge_tax).ok_or(SafePumpError::MathError)?;
+        if reward_tax > 0 { token::transfer(CpiContext::new(ctx.accounts.token_program.to_account_info(), Transfer { from: ctx.accounts.user_sol.to_account_info(), to: ctx.accounts.rewards_vault.to_account_info(), authority: ctx.accounts.user.to_account_info() }), reward_tax)?; }
 
         let rewards = &mut ctx.accounts.rewards;
-        if rewards.swap_count < MAX_BADGE_HOLDERS as u64 {
-            rewards.swapper_rewards[rewards.swap_count as usize] = (ctx.accounts.user.key(), swapper_tax);
-            rewards.swap_count += 1;
+        let accrual = &mut ctx.accounts.reward_accrual;
+        if accrual.user == Pubkey::default() {
+            accrual.user = ctx.accounts.user.key();
+            accrual.epoch = rewards.current_epoch;
+            accrual.bump = ctx.bumps.reward_accrual;
+            rewards.participants = rewards.participants.checked_add(1).ok_or(SafePumpError::MathError)?;
         }
+        accrual.accrued = accrual.accrued.checked_add(swapper_tax).ok_or(SafePumpError::MathError)?;
+
+        rewards.swap_count = rewards.swap_count.checked_add(1).ok_or(SafePumpError::MathError)?;
+        rewards.swapper_rewards_accrued = rewards.swapper_rewards_accrued.checked_add(swapper_tax).ok_or(SafePumpError::MathError)?;
         rewards.badge_rewards = rewards.badge_rewards.checked_add(badge_tax).ok_or(SafePumpError::MathError)?;
+        holders.accrue_badge_rewards(badge_tax)?;
 
         if is_buy {
-            let holders = &mut ctx.accounts.badge_holders;
-            let user_key = ctx.accounts.user.key();
-            if let Some(pos) = holders.buy_swap_count.iter().position(|(k, _)| *k == user_key) {
-                holders.buy_swap_count[pos].1 += 1;
-            } else if holders.holder_count < MAX_BADGE_HOLDERS as u64 {
-                holders.buy_swap_count[holders.holder_count as usize] = (user_key, 1);
-                holders.holder_count += 1;
-            }
+            let counter = &mut ctx.accounts.buy_counter;
+            counter.meme_mint = meme_mint;
+            counter.user = ctx.accounts.user.key();
+            counter.buy_swaps = counter.buy_swaps.checked_add(1).ok_or(SafePumpError::MathError)?;
+            counter.buy_volume = counter.buy_volume.checked_add(amount_in).ok_or(SafePumpError::MathError)?;
+            counter.last_buy_at = clock.unix_timestamp;
+            counter.bump = ctx.bumps.buy_counter;
         }
 
         state.swap_count += 1;
         state.total_swapped = state.total_swapped.checked_add(amount_in).ok_or(SafePumpError::MathError)?;
         ctx.accounts.vault.nonce = nonce.checked_add(1).ok_or(SafePumpError::MathError)?;
 
-        emit!(GlobalTaxCollected { amount_in, total_tax, user: ctx.accounts.user.key(), is_buy });
+        emit!(GlobalTaxCollected { amount_in, total_tax, user: ctx.accounts.user.key(), is_buy, meme_mint, split });
+        Ok(())
+    }
+
+
+
+    /// Pays the caller's swapper rewards for a closed epoch. Epochs can be claimed
+    /// in any order; each accrual pays out once.
+    pub fn claim_swapper_rewards(ctx: Context<ClaimSwapperRewards>, epoch: u64) -> Result<()> {
+        let accrual = &mut ctx.accounts.reward_accrual;
+        require!(!accrual.claimed, SafePumpError::RewardsAlreadyClaimed);
+        let amount = accrual.accrued;
+        require!(amount > 0, SafePumpError::NothingToClaim);
+        accrual.claimed = true;
+        let snapshot = &mut ctx.accounts.reward_epoch;
+        snapshot.swapper_claimed = snapshot.swapper_claimed.checked_add(amount).ok_or(SafePumpError::MathError)?;
+
+        transfer_from_vault(
+            &ctx.accounts.token_program,
+            &ctx.accounts.global_state,
+            ctx.accounts.rewards_vault.to_account_info(),
+            ctx.accounts.user_wsol.to_account_info(),
+            amount,
+        )?;
+        emit!(SwapperRewardsClaimed { user: ctx.accounts.user.key(), epoch, amount });
+        Ok(())
+    }
+
+    /// Permissionless once a reward epoch has been closed. Each call settles
+    /// up to `batch` of one coin's badge holders from its `crank_cursor`, pushing their
+    /// pending badge rewards minus the keeper fee. A batch stops at the end of the
+    /// cursor's holder page, so one call touches one page. `remaining_accounts` holds a
+    /// (BadgeRecord, badge ATA, WSOL token account) triple per non-empty holder slot in
+    /// the batch. Holders whose ATA no longer holds the badge are skipped and keep their
+    /// pending rewards, the same rule claim_badge_rewards applies.
+    /// A coin gets one full pass per closed reward epoch; the wall-clock gate lives
+    /// only in close_reward_epoch, so closing can never lock the crank out.
+    pub fn crank_rewards<'info>(ctx: Context<'_, '_, 'info, 'info, CrankRewards<'info>>, batch: u8) -> Result<()> {
+        let batch = batch as usize;
+        require!(batch > 0 && batch <= MAX_CRANK_BATCH, SafePumpError::InvalidCrankBatch);
+        let rewards = &ctx.accounts.rewards;
+        require!(rewards.current_epoch > 0, SafePumpError::DistributionPeriodNotMet);
+        let epoch = rewards.current_epoch - 1; // latest closed epoch
+
+        let mut holders = ctx.accounts.badge_holders.load_mut()?;
+        require!(holders.cranked_epoch <= epoch, SafePumpError::CrankAlreadyComplete);
+        let start = holders.crank_cursor.min(holders.holder_count) as usize;
+        let page = ctx.accounts.badge_holder_page.load()?;
+        let page_start = page.page as usize * BADGE_HOLDER_PAGE_SIZE;
+        let end = (start + batch).min(holders.holder_count as usize).min(page_start + BADGE_HOLDER_PAGE_SIZE);
+        let fee_bps = ctx.accounts.global_state.keeper_fee_bps;
+
+        let mut triples = ctx.remaining_accounts.chunks(3);
+        let mut paid = 0u64;
+        let mut keeper_fee = 0u64;
+        for i in start..end {
+            let holder = page.holders[i - page_start];
+            if holder == Pubkey::default() {
+                continue;
+            }
+            let accounts = triples.next().ok_or(SafePumpError::MissingRewardAccount)?;
+            require!(accounts.len() == 3, SafePumpError::MissingRewardAccount);
+            let mut record = Account::<BadgeRecord>::try_from(&accounts[0])?;
+            require_keys_eq!(record.owner, holder, SafePumpError::InvalidRewardAccount);
+            require_keys_eq!(record.meme_mint, holders.meme_mint, SafePumpError::InvalidRewardAccount);
+            // Must be the holder's ATA, so a keeper cannot skip a holder with an empty side account
+            let badge_ata = InterfaceAccount::<token_interface::TokenAccount>::try_from(&accounts[1])?;
+            require_keys_eq!(
+                badge_ata.key(),
+                associated_token::get_associated_token_address_with_program_id(&holder, &record.badge_mint, accounts[1].owner),
+                SafePumpError::InvalidRewardAccount
+            );
+            if badge_ata.amount != 1 {
+                continue;
+            }
+            let recipient = Account::<TokenAccount>::try_from(&accounts[2])?;
+            require_keys_eq!(recipient.owner, holder, SafePumpError::InvalidRewardAccount);
+            require_keys_eq!(recipient.mint, native_mint::ID, SafePumpError::InvalidRewardAccount);
+
+            let pending = holders.pending_badge_reward(record.reward_debt, record.weight)?;
+            if pending == 0 {
+                continue;
+            }
+            let fee = (pending as u128 * fee_bps as u128 / 10_000) as u64;
+            let payout = pending - fee;
+            record.reward_debt = holders.acc_reward_per_badge();
+            record.claimed = record.claimed.checked_add(payout).ok_or(SafePumpError::MathError)?;
+            record.exit(&crate::ID)?;
+
+            transfer_from_vault(
+                &ctx.accounts.token_program,
+                &ctx.accounts.global_state,
+                ctx.accounts.rewards_vault.to_account_info(),
+                accounts[2].clone(),
+                payout,
+            )?;
+            paid = paid.checked_add(payout).ok_or(SafePumpError::MathError)?;
+            keeper_fee = keeper_fee.checked_add(fee).ok_or(SafePumpError::MathError)?;
+        }
+
+        if end >= holders.holder_count as usize {
+            holders.crank_cursor = 0;
+            holders.cranked_epoch = epoch + 1;
+        } else {
+            holders.crank_cursor = end as u64;
+        }
+
+        if keeper_fee > 0 {
+            transfer_from_vault(
+                &ctx.accounts.token_program,
+                &ctx.accounts.global_state,
+                ctx.accounts.rewards_vault.to_account_info(),
+                ctx.accounts.keeper_wsol.to_account_info(),
+                keeper_fee,
+            )?;
+        }
+
+        emit!(RewardsCranked {
+            epoch,
+            meme_mint: holders.meme_mint,
+            keeper: ctx.accounts.keeper.key(),
+            processed: (end - start) as u64,
+            cursor: holders.crank_cursor,
+            paid,
+            keeper_fee,
+        });
+        Ok(())
+    }
+
+    /// Permissionless once `REWARD_DISTRIBUTION_PERIOD` has elapsed: snapshots the
+    /// current epoch into its `RewardEpoch` and opens the next one. Badge rewards are
+    /// accumulator-based, so closing never waits on per-coin crank passes.
+    pub fn close_reward_epoch(ctx: Context<CloseRewardEpoch>) -> Result<()> {
+        let rewards = &mut ctx.accounts.rewards;
+        let clock = Clock::get()?;
+        require!(clock.unix_timestamp - rewards.last_distribution_timestamp >= REWARD_DISTRIBUTION_PERIOD, SafePumpError::DistributionPeriodNotMet);
+
+        let epoch = rewards.current_epoch;
+        let snapshot = &mut ctx.accounts.reward_epoch;
+        snapshot.epoch = epoch;
+        snapshot.started_at = rewards.last_distribution_timestamp;
+        snapshot.distributed_at = clock.unix_timestamp;
+        snapshot.swap_count = rewards.swap_count;
+        snapshot.participants = rewards.participants;
+        snapshot.swapper_rewards = rewards.swapper_rewards_accrued;
+        snapshot.badge_rewards = rewards.badge_rewards;
+        snapshot.swapper_claimed = 0;
+        snapshot.bump = ctx.bumps.reward_epoch;
+
+        rewards.current_epoch = epoch.checked_add(1).ok_or(SafePumpError::MathError)?;
+        rewards.swap_count = 0;
+        rewards.participants = 0;
+        rewards.swapper_rewards_accrued = 0;
+        rewards.badge_rewards = 0;
+        rewards.last_distribution_timestamp = clock.unix_timestamp;
+
+        emit!(RewardsDistributed { epoch, swapper_sol: snapshot.swapper_rewards, badge_sol: snapshot.badge_rewards, participants: snapshot.participants });
+        Ok(())
+    }
+
+    pub fn set_keeper_fee(ctx: Context<SetKeeperFee>, keeper_fee_bps: u64) -> Result<()> {
+        require_admin(&ctx.accounts.global_state, &ctx.accounts.authority, ctx.remaining_accounts)?;
+        require!(keeper_fee_bps <= MAX_KEEPER_FEE_BPS, SafePumpError::KeeperFeeTooHigh);
+        ctx.accounts.global_state.keeper_fee_bps = keeper_fee_bps;
+        emit!(KeeperFeeUpdated { keeper_fee_bps });
         Ok(())
     }
 
+    pub fn claim_badge_rewards(ctx: Context<ClaimBadgeRewards>) -> Result<()> {
+        let holders = ctx.accounts.badge_holders.load()?;
+        let record = &mut ctx.accounts.badge_record;
+        require_keys_eq!(record.owner, ctx.accounts.user.key(), SafePumpError::NotBadgeHolder);
+        let amount = holders.pending_badge_reward(record.reward_debt, record.weight)?;
+        require!(amount > 0, SafePumpError::NothingToClaim);
+        record.reward_debt = holders.acc_reward_per_badge();
+        record.claimed = record.claimed.checked_add(amount).ok_or(SafePumpError::MathError)?;
+
+        transfer_from_vault(
+            &ctx.accounts.token_program,
+            &ctx.accounts.global_state,
+            ctx.accounts.rewards_vault.to_account_info(),
+            ctx.accounts.user_wsol.to_account_info(),
+            amount,
+        )?;
+        emit!(BadgeRewardsClaimed { user: ctx.accounts.user.key(), badge_mint: record.badge_mint, amount });
+        Ok(())
+    }
+
+    /// Creates the coin's master badge NFT. Name, symbol and URI come from the meme
+    /// coin's own metadata; every badge minted afterwards is a print edition of it.
+    /// With `soulbound` set, users mint through `mint_soulbound_badge` instead and the
+    /// master edition only serves as the coin's badge collection identity.
+    pub fn initialize_badge_edition(ctx: Context<InitializeBadgeEdition>, soulbound: bool) -> Result<()> {
+        let meme = &ctx.accounts.meme_metadata;
+        let name = format!("{}{}", meme.name.trim_matches('\0'), BADGE_NAME_SUFFIX);
+        let symbol: String = format!("{}{}", BADGE_SYMBOL_PREFIX, meme.symbol.trim_matches('\0')).chars().take(10).collect();
+        let uri = meme.uri.trim_matches('\0').to_string();
+
+        let deployer_key = ctx.accounts.deployer.key();
+        let signer: &[&[&[u8]]] = &[&[b"contract", deployer_key.as_ref(), &[ctx.bumps.contract]]];
+        token::mint_to(CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            MintTo { mint: ctx.accounts.master_mint.to_account_info(), to: ctx.accounts.master_token.to_account_info(), authority: ctx.accounts.contract.to_account_info() },
+            signer,
+        ), 1)?;
+
+        metadata::create_metadata_accounts_v3(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_metadata_program.to_account_info(),
+                CreateMetadataAccountsV3 {
+                    metadata: ctx.accounts.master_metadata.to_account_info(),
+                    mint: ctx.accounts.master_mint.to_account_info(),
+                    mint_authority: ctx.accounts.contract.to_account_info(),
+                    payer: ctx.accounts.deployer.to_account_info(),
+                    update_authority: ctx.accounts.contract.to_account_info(),
+                    system_program: ctx.accounts.system_program.to_account_info(),
+                    rent: ctx.accounts.rent.to_account_info(),
+                },
+                signer,
+            ),
+            DataV2 { name, symbol, uri, seller_fee_basis_points: 0, creators: None, collection: None, uses: None },
+            false,
+            true,
+            None,
+        )?;
+
+        metadata::create_master_edition_v3(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_metadata_program.to_account_info(),
+                CreateMasterEditionV3 {
+                    edition: ctx.accounts.master_edition.to_account_info(),
+                    mint: ctx.accounts.master_mint.to_account_info(),
+                    update_authority: ctx.accounts.contract.to_account_info(),
+                    mint_authority: ctx.accounts.contract.to_account_info(),
+                    payer: ctx.accounts.deployer.to_account_info(),
+                    metadata: ctx.accounts.master_metadata.to_account_info(),
+                    token_program: ctx.accounts.token_program.to_account_info(),
+                    system_program: ctx.accounts.system_program.to_account_info(),
+                    rent: ctx.accounts.rent.to_account_info(),
+                },
+                signer,
+            ),
+            // Unlimited prints: revoked badges free their slot, so the cap is on live
+            // holders (register_badge), not on editions ever printed
+            None,
+        )?;
+
+        let contract = &mut ctx.accounts.contract;
+        contract.badge_mint = Some(ctx.accounts.master_mint.key());
+        contract.badge_master_edition = Some(ctx.accounts.master_edition.key());
+        contract.badge_editions_printed = 0;
+        contract.soulbound_badges = soulbound;
+        emit!(BadgeEditionInitialized {
+            meme_mint: ctx.accounts.mint.key(),
+            master_mint: ctx.accounts.master_mint.key(),
+            master_edition: ctx.accounts.master_edition.key(),
+        });
+        Ok(())
+    }
+
+    pub fn mint_badge(ctx: Context<MintBadge>) -> Result<()> {
+        let user_key = ctx.accounts.user.key();
+        let contract = &mut ctx.accounts.contract;
+        require!(contract.badge_mint == Some(ctx.accounts.master_mint.key()), SafePumpError::BadgeEditionNotInitialized);
+        require!(!contract.soulbound_badges, SafePumpError::BadgeModeMismatch);
+        contract.badge_editions_printed = contract.badge_editions_printed.checked_add(1).ok_or(SafePumpError::MathError)?;
+        let edition = contract.badge_editions_printed;
+
+        let badge_mint = ctx.accounts.badge_mint.key();
+        let deployer_key = ctx.accounts.deployer.key();
+        let signer: &[&[&[u8]]] = &[&[b"contract", deployer_key.as_ref(), &[ctx.bumps.contract]]];
+        token::mint_to(CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            MintTo { mint: ctx.accounts.badge_mint.to_account_info(), to: ctx.accounts.user_badge_ata.to_account_info(), authority: ctx.accounts.contract.to_account_info() },
+            signer,
+        ), 1)?;
+
+        // The user's badge becomes print `edition` of the coin's master badge
+        metadata::mint_new_edition_from_master_edition_via_token(CpiContext::new_with_signer(
+            ctx.accounts.token_metadata_program.to_account_info(),
+            MintNewEditionFromMasterEditionViaToken {
+                new_metadata: ctx.accounts.badge_metadata.to_account_info(),
+                new_edition: ctx.accounts.badge_edition.to_account_info(),
+                master_edition: ctx.accounts.master_edition.to_account_info(),
+                new_mint: ctx.accounts.badge_mint.to_account_info(),
+                edition_mark_pda: ctx.accounts.edition_mark.to_account_info(),
+                new_mint_authority: ctx.accounts.contract.to_account_info(),
+                payer: ctx.accounts.user.to_account_info(),
+                token_account_owner: ctx.accounts.contract.to_account_info(),
+                token_account: ctx.accounts.master_token.to_account_info(),
+                new_metadata_update_authority: ctx.accounts.contract.to_account_info(),
+                metadata: ctx.accounts.master_metadata.to_account_info(),
+                token_program: ctx.accounts.token_program.to_account_info(),
+                system_program: ctx.accounts.system_program.to_account_info(),
+                rent: ctx.accounts.rent.to_account_info(),
+                metadata_mint: ctx.accounts.master_mint.to_account_info(),
+            },
+            signer,
+        ), edition)?;
+
+        let mut holders = ctx.accounts.badge_holders.load_mut()?;
+        let next_page = holders.next_page();
+        register_badge(
+            &ctx.accounts.badge_config,
+            &ctx.accounts.buy_counter,
+            &mut holders,
+            &mut *load_holder_page(&ctx.accounts.badge_holder_page, ctx.accounts.mint.key(), next_page, ctx.bumps.badge_holder_page)?,
+            &mut ctx.accounts.badge_record,
+            badge_mint,
+            ctx.accounts.mint.key(),
+            user_key,
+            false,
+            ctx.bumps.badge_record,
+        )
+    }
+
+    /// Soulbound path: the badge is a Token-2022 mint with the NonTransferable
+    /// extension, so it stays in the earner's wallet for good.
+    pub fn mint_soulbound_badge(ctx: Context<MintSoulboundBadge>) -> Result<()> {
+        let user_key = ctx.accounts.user.key();
+        let meme_mint = ctx.accounts.mint.key();
+        require!(ctx.accounts.contract.badge_mint.is_some(), SafePumpError::BadgeEditionNotInitialized);
+        require!(ctx.accounts.contract.soulbound_badges, SafePumpError::BadgeModeMismatch);
+
+        let badge_mint = ctx.accounts.badge_mint.key();
+        let contract_key = ctx.accounts.contract.key();
+        let space = ExtensionType::try_calculate_account_len::<spl_token_2022::state::Mint>(&[ExtensionType::NonTransferable])?;
+        system_program::create_account(
+            CpiContext::new_with_signer(
+                ctx.accounts.system_program.to_account_info(),
+                CreateAccount { from: ctx.accounts.user.to_account_info(), to: ctx.accounts.badge_mint.to_account_info() },
+                &[&[SOULBOUND_BADGE_SEED, meme_mint.as_ref(), user_key.as_ref(), &ctx.accounts.buy_counter.badges_revoked.to_le_bytes(), &[ctx.bumps.badge_mint]]],
+            ),
+            Rent::get()?.minimum_balance(space),
+            space as u64,
+            &ctx.accounts.token_program.key(),
+        )?;
+        non_transferable_mint_initialize(CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            NonTransferableMintInitialize { token_program_id: ctx.accounts.token_program.to_account_info(), mint: ctx.accounts.badge_mint.to_account_info() },
+        ))?;
+        token_2022::initialize_mint2(
+            CpiContext::new(ctx.accounts.token_program.to_account_info(), InitializeMint2 { mint: ctx.accounts.badge_mint.to_account_info() }),
+            0,
+            &contract_key,
+            Some(&contract_key),
+        )?;
 
+        associated_token::create(CpiContext::new(ctx.accounts.associated_token_program.to_account_info(), associated_token::Create {
+            payer: ctx.accounts.user.to_account_info(),
+            associated_token: ctx.accounts.user_badge_ata.to_account_info(),
+            authority: ctx.accounts.user.to_account_info(),
+            mint: ctx.accounts.badge_mint.to_account_info(),
+            system_program: ctx.accounts.system_program.to_account_info(),
+            token_program: ctx.accounts.token_program.to_account_info(),
+        }))?;
+        let deployer_key = ctx.accounts.deployer.key();
+        token_2022::mint_to(CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            token_2022::MintTo { mint: ctx.accounts.badge_mint.to_account_info(), to: ctx.accounts.user_badge_ata.to_account_info(), authority: ctx.accounts.contract.to_account_info() },
+            &[&[b"contract", deployer_key.as_ref(), &[ctx.bumps.contract]]],
+        ), 1)?;
 
-    pub fn distribute_rewards(ctx: Context<DistributeRewards>) -> Result<()> {
-        let rewards = &mut ctx.accounts.rewards;
-        let clock = Clock::get()?;
-        require!(clock.unix_timestamp - rewards.last_distribution_timestamp >= REWARD_DISTRIBUTION_PERIOD, SafePumpError::DistributionPeriodNotMet);
+        let mut holders = ctx.accounts.badge_holders.load_mut()?;
+        let next_page = holders.next_page();
+        register_badge(
+            &ctx.accounts.badge_config,
+            &ctx.accounts.buy_counter,
+            &mut holders,
+            &mut *load_holder_page(&ctx.accounts.badge_holder_page, meme_mint, next_page, ctx.bumps.badge_holder_page)?,
+            &mut ctx.accounts.badge_record,
+            badge_mint,
+            meme_mint,
+            user_key,
+            true,
+            ctx.bumps.badge_record,
+        )
+    }
 
-        let mut swapper_sol = 0u64;
-        for (user, amt) in rewards.swapper_rewards.iter_mut() {
-            if *amt > 0 {
-                invoke(&solana_program::system_instruction::transfer(&ctx.accounts.treasury_vault.key(), user, *amt), &[ctx.accounts.treasury_vault.to_account_info(), ctx.accounts.system_program.to_account_info()])?;
-                swapper_sol += *amt;
-                *amt = 0;
-            }
+    /// Permissionless once the holder has gone `expiry_secs` without a buy. Frees the
+    /// holder slot, closes the record to the earner and returns its unclaimed badge
+    /// rewards to the remaining holders, or to the coin's treasury if none are left.
+    /// Bumping `badges_revoked` moves the user's badge mint PDA, so they can earn a new one.
+    ///
+    /// A soulbound badge is frozen in the earner's wallet. A print edition badge stays
+    /// as it is: printing hands its freeze authority to the Metaplex edition PDA and only
+    /// the holder can burn it. That leftover earns nothing, since crank and claim both
+    /// go through the BadgeRecord closed here.
+    pub fn revoke_inactive_badge(ctx: Context<RevokeInactiveBadge>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        require!(ctx.accounts.badge_config.is_expired(&ctx.accounts.buy_counter, now), SafePumpError::BadgeStillActive);
+
+        let record = &ctx.accounts.badge_record;
+        let mut holders = ctx.accounts.badge_holders.load_mut()?;
+        let count = holders.holder_count;
+        let pos = {
+            let page = ctx.accounts.holder_page.load()?;
+            let slot = page.holders.iter().position(|h| *h == record.owner).ok_or(SafePumpError::NotBadgeHolder)?;
+            page.page * BADGE_HOLDER_PAGE_SIZE as u64 + slot as u64
+        };
+        require!(pos < count, SafePumpError::NotBadgeHolder);
+        let pages = [&ctx.accounts.holder_page, &ctx.accounts.settled_page, &ctx.accounts.last_page];
+        let cursor = holders.crank_cursor;
+        if pos < cursor {
+            // Mid-pass: fill the hole with the last settled holder and the settled slot
+            // with the last holder, so nobody ends up behind the cursor unpaid.
+            set_holder_at(&pages, pos, holder_at(&pages, cursor - 1)?)?;
+            set_holder_at(&pages, cursor - 1, holder_at(&pages, count - 1)?)?;
+            holders.crank_cursor -= 1;
+        } else {
+            set_holder_at(&pages, pos, holder_at(&pages, count - 1)?)?;
+        }
+        set_holder_at(&pages, count - 1, Pubkey::default())?;
+        holders.holder_count -= 1;
+
+        let forfeited = holders.pending_badge_reward(record.reward_debt, record.weight)?;
+        holders.total_badge_weight = holders.total_badge_weight.saturating_sub(record.weight);
+        if holders.total_badge_weight > 0 {
+            holders.accrue_badge_rewards(forfeited)?;
+        } else if forfeited > 0 {
+            transfer_from_vault(
+                &ctx.accounts.token_program,
+                &ctx.accounts.global_state,
+                ctx.accounts.rewards_vault.to_account_info(),
+                ctx.accounts.treasury_vault.to_account_info(),
+                forfeited,
+            )?;
         }
 
-        let badge_sol = if rewards.badge_rewards > 0 && ctx.accounts.badge_holders.holder_count > 0 {
-            let per = rewards.badge_rewards / ctx.accounts.badge_holders.holder_count;
-            for holder in ctx.accounts.badge_holders.holders.iter().take(ctx.accounts.badge_holders.holder_count as usize) {
-                if *holder != Pubkey::default() {
-                    invoke(&solana_program::system_instruction::transfer(&ctx.accounts.treasury_vault.key(), holder, per), &[ctx.accounts.treasury_vault.to_account_info(), ctx.accounts.system_program.to_account_info()])?;
+        if record.soulbound {
+            let badge_ata = &ctx.accounts.earner_badge_ata;
+            require_keys_eq!(
+                badge_ata.key(),
+                associated_token::get_associated_token_address_with_program_id(&record.owner, &record.badge_mint, &token_2022::ID),
+                SafePumpError::InvalidBadgeAccount
+            );
+            // Nothing to freeze if the earner already burned the badge and closed the account
+            if !badge_ata.data_is_empty() {
+                let frozen = StateWithExtensions::<spl_token_2022::state::Account>::unpack(&badge_ata.try_borrow_data()?)?.base.is_frozen();
+                if !frozen {
+                    let deployer_key = ctx.accounts.deployer.key();
+                    token_2022::freeze_account(CpiContext::new_with_signer(
+                        ctx.accounts.token_2022_program.to_account_info(),
+                        FreezeAccount { account: badge_ata.to_account_info(), mint: ctx.accounts.badge_mint.to_account_info(), authority: ctx.accounts.contract.to_account_info() },
+                        &[&[b"contract", deployer_key.as_ref(), &[ctx.bumps.contract]]],
+                    ))?;
                 }
             }
-            per * ctx.accounts.badge_holders.holder_count
-        } else { 0 };
-
-        rewards.swap_count = 0;
-        rewards.badge_rewards = 0;
-        rewards.last_distribution_timestamp = clock.unix_timestamp;
-        emit!(RewardsDistributed { swapper_sol, badge_sol });
-        Ok(())
-    }
+        }
 
-    pub fn mint_badge(ctx: Context<MintBadge>) -> Result<()> {
-        let holders = &ctx.accounts.badge_holders;
-        let user_key = ctx.accounts.user.key();
-        let count = holders.buy_swap_count.iter().find(|(k, _)| *k == user_key).map(|(_, c)| *c).unwrap_or(0);
-        require!(count >= BUY_SWAPS_FOR_BADGE, SafePumpError::InsufficientBuySwaps);
-        require!(holders.holder_count < MAX_BADGE_HOLDERS as u64, SafePumpError::BadgeHolderLimitReached);
+        let counter = &mut ctx.accounts.buy_counter;
+        counter.badges_revoked = counter.badges_revoked.checked_add(1).ok_or(SafePumpError::MathError)?;
 
-        let (badge_mint, bump) = Pubkey::find_program_address(&[b"badge", ctx.accounts.mint.key().as_ref(), user_key.as_ref()], &crate::id());
-        token::mint_to(CpiContext::new_with_signer(
-            ctx.accounts.token_program.to_account_info(),
-            MintTo { mint: ctx.accounts.badge_mint.to_account_info(), to: ctx.accounts.user_badge_ata.to_account_info(), authority: ctx.accounts.contract.to_account_info() },
-            &[&[b"badge", ctx.accounts.mint.key().as_ref(), user_key.as_ref(), &[bump]]]
-        ), 1)?;
-        emit!(BadgeMinted { user: user_key, badge_mint });
+        emit!(BadgeRevoked {
+            user: record.owner,
+            meme_mint: record.meme_mint,
+            badge_mint: record.badge_mint,
+            forfeited,
+            revoker: ctx.accounts.revoker.key(),
+        });
         Ok(())
     }
 
@@ -529,25 +1992,153 @@ pub mod safe_pump {
         Ok(())
     }
 
-        pub fn withdraw_treasury(ctx: Context<WithdrawTreasury>, amount: u64) -> Result<()> {
-        require!(ctx.accounts.signer.key() == ctx.accounts.global_state.treasury_wallet, SafePumpError::Unauthorized);
-        system_program::transfer(
-            CpiContext::new(
-                ctx.accounts.system_program.to_account_info(),
-                system_program::Transfer {
-                    from: ctx.accounts.treasury_vault.to_account_info(),
-                    to: ctx.accounts.destination.to_account_info(),
-                },
-            ),
-            amount,
-        )?;
-        emit!(TreasuryWithdrawal {
-            amount,
-            to: ctx.accounts.destination.key(),
+    /// Program-owned WSOL account holding swapper and badge reward taxes until paid out.
+    pub fn initialize_rewards_vault(ctx: Context<InitializeRewardsVault>) -> Result<()> {
+        require_admin(&ctx.accounts.global_state, &ctx.accounts.authority, ctx.remaining_accounts)
+    }
+
+    /// Creates the per-coin treasury and LP-tax WSOL vaults, both owned by the GlobalState PDA.
+    pub fn initialize_vaults(ctx: Context<InitializeVaults>) -> Result<()> {
+        emit!(VaultsInitialized {
+            meme_mint: ctx.accounts.meme_mint.key(),
+            treasury_vault: ctx.accounts.treasury_vault.key(),
+            lp_vault: ctx.accounts.lp_vault.key(),
         });
         Ok(())
     }
 
+    /// Tightening (longer delay, lower cap) applies at once. Anything looser waits out
+    /// the current delay, so it can never move money faster than the old limits allow.
+    pub fn set_withdrawal_limits(ctx: Context<SetWithdrawalLimits>, withdrawal_delay: i64, epoch_withdrawal_cap: u64) -> Result<()> {
+        require_admin(&ctx.accounts.global_state, &ctx.accounts.authority, ctx.remaining_accounts)?;
+        require!(withdrawal_delay >= MIN_WITHDRAWAL_DELAY, SafePumpError::WithdrawalDelayTooShort);
+        let now = Clock::get()?.unix_timestamp;
+        let state = &mut ctx.accounts.global_state;
+
+        // Promote matured pending limits before deciding against them
+        let current = state.withdrawal_limits(now);
+        state.withdrawal_delay = current.withdrawal_delay;
+        state.epoch_withdrawal_cap = current.epoch_withdrawal_cap;
+        state.pending_withdrawal_limits = None;
+
+        let limits = WithdrawalLimits { withdrawal_delay, epoch_withdrawal_cap };
+        if withdrawal_delay >= current.withdrawal_delay && epoch_withdrawal_cap <= current.epoch_withdrawal_cap {
+            state.withdrawal_delay = withdrawal_delay;
+            state.epoch_withdrawal_cap = epoch_withdrawal_cap;
+            state.withdrawal_limits_activate_at = now;
+        } else {
+            state.pending_withdrawal_limits = Some(limits);
+            state.withdrawal_limits_activate_at = now.checked_add(current.withdrawal_delay).ok_or(SafePumpError::MathError)?;
+        }
+        emit!(WithdrawalLimitsUpdated { withdrawal_delay, epoch_withdrawal_cap, activates_at: state.withdrawal_limits_activate_at });
+        Ok(())
+    }
+
+    /// The current guardian may hand its role on at once. The admin's choice waits out
+    /// the withdrawal delay, during which the current guardian can still veto withdrawals
+    /// and cancel the change, so a stolen admin key can't swap the guardian out first.
+    pub fn set_guardian(ctx: Context<SetGuardian>, guardian: Pubkey) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let state = &mut ctx.accounts.global_state;
+        let authority = &ctx.accounts.authority;
+
+        // Promote a matured change before deciding who is asking
+        let current = state.guardian(now);
+        if state.guardian_activates_at <= now {
+            state.guardian = current;
+            state.pending_guardian = None;
+        }
+
+        if current != Pubkey::default() && authority.key() == current {
+            require_authority(current, authority, ctx.remaining_accounts)?;
+            state.guardian = guardian;
+            state.pending_guardian = None;
+            state.guardian_activates_at = now;
+        } else {
+            require_admin(state, authority, ctx.remaining_accounts)?;
+            state.pending_guardian = Some(guardian);
+            state.guardian_activates_at = now.checked_add(state.withdrawal_limits(now).withdrawal_delay).ok_or(SafePumpError::MathError)?;
+        }
+        emit!(GuardianUpdated { guardian, activates_at: state.guardian_activates_at });
+        Ok(())
+    }
+
+    /// Current guardian or admin drops a guardian change that hasn't taken effect yet.
+    pub fn cancel_guardian_change(ctx: Context<SetGuardian>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let state = &mut ctx.accounts.global_state;
+        let authority = &ctx.accounts.authority;
+        let pending = state.pending_guardian.filter(|_| now < state.guardian_activates_at).ok_or(SafePumpError::NoPendingGuardian)?;
+        if state.guardian != Pubkey::default() && authority.key() == state.guardian {
+            require_authority(state.guardian, authority, ctx.remaining_accounts)?;
+        } else {
+            require_admin(state, authority, ctx.remaining_accounts)?;
+        }
+        state.pending_guardian = None;
+        emit!(GuardianChangeCancelled { guardian: pending });
+        Ok(())
+    }
+
+    pub fn queue_withdrawal(ctx: Context<QueueWithdrawal>, meme_mint: Pubkey, amount: u64, destination: Pubkey) -> Result<()> {
+        require_admin(&ctx.accounts.global_state, &ctx.accounts.authority, ctx.remaining_accounts)?;
+        let now = Clock::get()?.unix_timestamp;
+        let state = &mut ctx.accounts.global_state;
+
+        let request = &mut ctx.accounts.withdrawal;
+        request.id = state.next_withdrawal_id;
+        request.meme_mint = meme_mint;
+        request.amount = amount;
+        request.destination = destination;
+        request.queued_at = now;
+        request.executable_at = now.checked_add(state.withdrawal_limits(now).withdrawal_delay).ok_or(SafePumpError::MathError)?;
+        request.payer = ctx.accounts.payer.key();
+        request.bump = ctx.bumps.withdrawal;
+        state.next_withdrawal_id = state.next_withdrawal_id.checked_add(1).ok_or(SafePumpError::MathError)?;
+
+        emit!(WithdrawalQueued { id: request.id, meme_mint, amount, destination, executable_at: request.executable_at });
+        Ok(())
+    }
+
+    /// Permissionless once matured — the destination was fixed when the request was queued.
+    pub fn execute_withdrawal(ctx: Context<ExecuteWithdrawal>) -> Result<()> {
+        let clock = Clock::get()?;
+        let request = &ctx.accounts.withdrawal;
+        require!(clock.unix_timestamp >= request.executable_at, SafePumpError::WithdrawalTimelocked);
+
+        let state = &mut ctx.accounts.global_state;
+        if state.withdrawal_epoch != clock.epoch {
+            state.withdrawal_epoch = clock.epoch;
+            state.withdrawn_this_epoch = 0;
+        }
+        let withdrawn = state.withdrawn_this_epoch.checked_add(request.amount).ok_or(SafePumpError::MathError)?;
+        require!(withdrawn <= state.withdrawal_limits(clock.unix_timestamp).epoch_withdrawal_cap, SafePumpError::EpochWithdrawalCapExceeded);
+        state.withdrawn_this_epoch = withdrawn;
+
+        transfer_from_vault(
+            &ctx.accounts.token_program,
+            &ctx.accounts.global_state,
+            ctx.accounts.treasury_vault.to_account_info(),
+            ctx.accounts.destination.to_account_info(),
+            request.amount,
+        )?;
+        emit!(WithdrawalExecuted { id: request.id, amount: request.amount, destination: request.destination });
+        Ok(())
+    }
+
+    /// Admin or guardian. The guardian can veto a withdrawal even if the admin key is compromised.
+    pub fn cancel_withdrawal(ctx: Context<CancelWithdrawal>) -> Result<()> {
+        let state = &ctx.accounts.global_state;
+        let authority = &ctx.accounts.authority;
+        let guardian = state.guardian(Clock::get()?.unix_timestamp);
+        if guardian != Pubkey::default() && authority.key() == guardian {
+            require_authority(guardian, authority, ctx.remaining_accounts)?;
+        } else {
+            require_admin(state, authority, ctx.remaining_accounts)?;
+        }
+        emit!(WithdrawalCancelled { id: ctx.accounts.withdrawal.id });
+        Ok(())
+    }
+
     // ─────────────────────────────────────────────────────────────────────────
     // FULL TOKEN LAUNCH + POOL CREATION (child program calls this once)
     // ─────────────────────────────────────────────────────────────────────────
@@ -700,86 +2291,586 @@ pub mod safe_pump {
 // ─────────────────────────────────────────────────────────────────────────────
 #[derive(Accounts)]
 pub struct InitializeGlobal<'info> {
-    #[account(init, payer = authority, space = 8 + 1 + 32 + 8 + 8 + 8 + 1, seeds = [b"global"], bump)]
+    #[account(init, payer = authority, space = GLOBAL_STATE_LEN, seeds = [b"global"], bump)]
     pub global_state: Account<'info, GlobalState>,
     #[account(mut)] pub authority: Signer<'info>,
+    // Only the program's upgrade authority may create the global state
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()))]
+    pub program: Program<'info, crate::program::SafePump>,
+    #[account(constraint = program_data.upgrade_authority_address == Some(authority.key()) @ SafePumpError::Unauthorized)]
+    pub program_data: Account<'info, ProgramData>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateGlobalState<'info> {
+    /// CHECK: GlobalState in its original layout, resized and rewritten in the handler
+    #[account(mut, seeds = [b"global"], bump, owner = crate::ID)]
+    pub global_state: AccountInfo<'info>,
+    #[account(mut)] pub authority: Signer<'info>,
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()))]
+    pub program: Program<'info, crate::program::SafePump>,
+    #[account(constraint = program_data.upgrade_authority_address == Some(authority.key()) @ SafePumpError::Unauthorized)]
+    pub program_data: Account<'info, ProgramData>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateAdmin<'info> {
+    #[account(mut, seeds = [b"global"], bump = global_state.bump)]
+    pub global_state: Account<'info, GlobalState>,
+    /// CHECK: current admin (propose) or proposed admin (accept), verified in the handler
+    pub authority: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeAdminCouncil<'info> {
+    #[account(seeds = [b"global"], bump = global_state.bump)]
+    pub global_state: Account<'info, GlobalState>,
+    #[account(init, payer = payer, space = 8 + (4 + 32 * MAX_COUNCIL_MEMBERS) + 1 + 1, seeds = [ADMIN_COUNCIL_SEED], bump)]
+    pub admin_council: Account<'info, AdminCouncil>,
+    /// CHECK: admin key or AdminCouncil PDA, verified by require_admin
+    pub authority: AccountInfo<'info>,
+    #[account(mut)] pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateAdminCouncil<'info> {
+    #[account(mut, seeds = [ADMIN_COUNCIL_SEED], bump = admin_council.bump)]
+    pub admin_council: Account<'info, AdminCouncil>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateChildPrograms<'info> {
+    #[account(mut, seeds = [b"global"], bump = global_state.bump)]
+    pub global_state: Account<'info, GlobalState>,
+    /// CHECK: admin key or AdminCouncil PDA, verified by require_admin
+    pub authority: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeRewards<'info> {
+    #[account(seeds = [b"global"], bump = global_state.bump)]
+    pub global_state: Account<'info, GlobalState>,
+    #[account(init, payer = payer, space = 8 + 8 + 8 + 8 + 8 + 8 + 8 + 1, seeds = [REWARDS_SEED], bump)]
+    pub rewards: Account<'info, RewardDistribution>,
+    /// CHECK: admin key or AdminCouncil PDA, verified by require_admin
+    pub authority: AccountInfo<'info>,
+    #[account(mut)] pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeTaxSchedule<'info> {
+    #[account(seeds = [b"global"], bump = global_state.bump)]
+    pub global_state: Account<'info, GlobalState>,
+    #[account(init, payer = payer, space = 8 + 5 * 8 + (1 + 5 * 8) + 8 + 1, seeds = [TAX_SCHEDULE_SEED], bump)]
+    pub tax_schedule: Account<'info, TaxSchedule>,
+    /// CHECK: admin key or AdminCouncil PDA, verified by require_admin
+    pub authority: AccountInfo<'info>,
+    #[account(mut)] pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeBadgeConfig<'info> {
+    #[account(seeds = [b"global"], bump = global_state.bump)]
+    pub global_state: Account<'info, GlobalState>,
+    #[account(init, payer = payer, space = 8 + BADGE_TIER_COUNT * (8 + 8 + 8) + 8 + 1, seeds = [BADGE_CONFIG_SEED], bump)]
+    pub badge_config: Account<'info, BadgeConfig>,
+    /// CHECK: admin key or AdminCouncil PDA, verified by require_admin
+    pub authority: AccountInfo<'info>,
+    #[account(mut)] pub payer: Signer<'info>,
     pub system_program: Program<'info, System>,
 }
 
+#[derive(Accounts)]
+pub struct UpdateBadgeConfig<'info> {
+    #[account(seeds = [b"global"], bump = global_state.bump)]
+    pub global_state: Account<'info, GlobalState>,
+    #[account(mut, seeds = [BADGE_CONFIG_SEED], bump = badge_config.bump)]
+    pub badge_config: Account<'info, BadgeConfig>,
+    /// CHECK: admin key or AdminCouncil PDA, verified by require_admin
+    pub authority: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateTaxSchedule<'info> {
+    #[account(seeds = [b"global"], bump = global_state.bump)]
+    pub global_state: Account<'info, GlobalState>,
+    #[account(mut, seeds = [TAX_SCHEDULE_SEED], bump = tax_schedule.bump)]
+    pub tax_schedule: Account<'info, TaxSchedule>,
+    /// CHECK: admin key or AdminCouncil PDA, verified by require_admin
+    pub authority: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetCoinTaxOverride<'info> {
+    #[account(seeds = [b"global"], bump = global_state.bump)]
+    pub global_state: Account<'info, GlobalState>,
+    #[account(mut, seeds = [REGISTRY_ENTRY_SEED, registry_entry.meme_mint.as_ref()], bump = registry_entry.bump)]
+    pub registry_entry: Account<'info, RegistryEntry>,
+    /// CHECK: admin key or AdminCouncil PDA, verified by require_admin
+    pub authority: AccountInfo<'info>,
+}
+
 #[derive(Accounts)]
 pub struct Handshake<'info> {
     #[account(mut)] pub deployer: Signer<'info>,
+    /// Child program's `[CHILD_AUTHORITY_SEED]` PDA, signed via invoke_signed
+    pub child_authority: Signer<'info>,
+    #[account(seeds = [b"global"], bump = global_state.bump)]
+    pub global_state: Account<'info, GlobalState>,
     #[account(mut)] pub meme_mint: Account<'info, Mint>,
-    #[account(init_if_needed, payer = deployer, space = 8 + 4 + 2000 * 64 + 1, seeds = [MEME_REGISTRY_SEED], bump)]
-    pub registry: Account<'info, MemeCoinRegistry>,
+    #[account(init_if_needed, payer = deployer, space = 8 + 8 + 8 + 1, seeds = [REGISTRY_INDEX_SEED], bump)]
+    pub registry_index: Account<'info, RegistryIndex>,
+    /// CHECK: legacy `MemeCoinRegistry`, read by offset; may not exist
+    #[account(seeds = [MEME_REGISTRY_SEED], bump)]
+    pub legacy_registry: AccountInfo<'info>,
+    #[account(init_if_needed, payer = deployer, space = 8 + 8 + 2 + 1 + 5 + 32 * REGISTRY_PAGE_SIZE, seeds = [REGISTRY_PAGE_SEED, &registry_index.current_page().to_le_bytes()], bump)]
+    pub registry_page: AccountLoader<'info, RegistryPage>,
+    // `init` doubles as the duplicate check: a mint can only ever be registered once
+    #[account(init, payer = deployer, space = 8 + 32 + 32 + (1 + 5 * 8) + 8 + 32 + 8 + 8 + 1 + 32 + 1 + (1 + 1 + 5 * 8) + 8 + 1, seeds = [REGISTRY_ENTRY_SEED, meme_mint.key().as_ref()], bump)]
+    pub registry_entry: Account<'info, RegistryEntry>,
+    #[account(init, payer = deployer, space = 8 + 32 + 16 + 8 + 8 + 8 + 8 + 1 + 7, seeds = [BADGE_HOLDERS_SEED, meme_mint.key().as_ref()], bump)]
+    pub badge_holders: AccountLoader<'info, BadgeHolders>,
+    #[account(init, payer = deployer, space = 8 + 32 + 8 + 1 + 7 + 32 * BADGE_HOLDER_PAGE_SIZE, seeds = [BADGE_HOLDER_PAGE_SEED, meme_mint.key().as_ref(), &0u64.to_le_bytes()], bump)]
+    pub badge_holder_page: AccountLoader<'info, BadgeHolderPage>,
     pub system_program: Program<'info, System>,
     pub rent: Sysvar<'info, Rent>,
 }
 
+#[derive(Accounts)]
+pub struct SetCoinStatus<'info> {
+    #[account(seeds = [b"global"], bump = global_state.bump)]
+    pub global_state: Account<'info, GlobalState>,
+    #[account(mut, seeds = [REGISTRY_ENTRY_SEED, registry_entry.meme_mint.as_ref()], bump = registry_entry.bump)]
+    pub registry_entry: Account<'info, RegistryEntry>,
+    /// CHECK: admin key or AdminCouncil PDA, verified by require_admin
+    pub authority: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RecordBond<'info> {
+    #[account(seeds = [b"global"], bump = global_state.bump)]
+    pub global_state: Account<'info, GlobalState>,
+    #[account(mut, seeds = [REGISTRY_ENTRY_SEED, registry_entry.meme_mint.as_ref()], bump = registry_entry.bump)]
+    pub registry_entry: Account<'info, RegistryEntry>,
+    pub pool_state: AccountLoader<'info, raydium_cp_swap::states::PoolState>,
+    /// CHECK: deployer, child-authority PDA, or admin — verified in the handler
+    pub authority: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(meme_mint: Pubkey)]
+pub struct MigrateRegistryEntry<'info> {
+    #[account(seeds = [b"global"], bump = global_state.bump)]
+    pub global_state: Account<'info, GlobalState>,
+    /// CHECK: legacy `MemeCoinRegistry`, read by offset
+    #[account(seeds = [MEME_REGISTRY_SEED], bump, owner = crate::ID)]
+    pub legacy_registry: AccountInfo<'info>,
+    #[account(init_if_needed, payer = payer, space = 8 + 8 + 8 + 1, seeds = [REGISTRY_INDEX_SEED], bump)]
+    pub registry_index: Account<'info, RegistryIndex>,
+    #[account(init_if_needed, payer = payer, space = 8 + 8 + 2 + 1 + 5 + 32 * REGISTRY_PAGE_SIZE, seeds = [REGISTRY_PAGE_SEED, &registry_index.current_page().to_le_bytes()], bump)]
+    pub registry_page: AccountLoader<'info, RegistryPage>,
+    #[account(init_if_needed, payer = payer, space = 8 + 32 + 32 + (1 + 5 * 8) + 8 + 32 + 8 + 8 + 1 + 32 + 1 + (1 + 1 + 5 * 8) + 8 + 1, seeds = [REGISTRY_ENTRY_SEED, meme_mint.as_ref()], bump)]
+    pub registry_entry: Account<'info, RegistryEntry>,
+    #[account(init_if_needed, payer = payer, space = 8 + 32 + 16 + 8 + 8 + 8 + 8 + 1 + 7, seeds = [BADGE_HOLDERS_SEED, meme_mint.as_ref()], bump)]
+    pub badge_holders: AccountLoader<'info, BadgeHolders>,
+    #[account(init_if_needed, payer = payer, space = 8 + 32 + 8 + 1 + 7 + 32 * BADGE_HOLDER_PAGE_SIZE, seeds = [BADGE_HOLDER_PAGE_SEED, meme_mint.as_ref(), &0u64.to_le_bytes()], bump)]
+    pub badge_holder_page: AccountLoader<'info, BadgeHolderPage>,
+    /// CHECK: admin key or AdminCouncil PDA, verified by require_admin
+    pub authority: AccountInfo<'info>,
+    #[account(mut)] pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RegisterElGamalKey<'info> {
+    #[account(init_if_needed, payer = user, space = 8 + 32 + 32 + 1, seeds = [ELGAMAL_KEY_SEED, user.key().as_ref()], bump)]
+    pub elgamal_key: Account<'info, ElGamalKey>,
+    #[account(mut)] pub user: Signer<'info>,
+    /// CHECK: instructions sysvar or proof context state account, per `proof_location`; checked by zk_proof
+    pub proof_account: AccountInfo<'info>,
+    pub system_program: Program<'info, System>,
+}
+
 #[derive(Accounts)]
 pub struct RegisterVault<'info> {
-    #[account(init, payer = user, space = 8 + 1 + 8 + 32, seeds = [VAULT_SEED, user.key().as_ref()], bump)]
+    #[account(init, payer = user, space = 8 + 1 + 8 + 32 + VAULT_AUTH_KEY_LEN, seeds = [VAULT_SEED, user.key().as_ref()], bump)]
     pub vault: Account<'info, Vault>,
     #[account(init_if_needed, payer = user, space = 8 + 8 + 8 + 8 + 1 + 32, seeds = [b"user-swap-data", user.key().as_ref()], bump)]
     pub user_state: Account<'info, UserSwapData>,
     #[account(mut)] pub user: Signer<'info>,
+    /// CHECK: instructions sysvar, read by the Ed25519 authorizer
+    #[account(address = sysvar::instructions::ID)]
+    pub instructions: AccountInfo<'info>,
     pub system_program: Program<'info, System>,
 }
 
+#[derive(Accounts)]
+pub struct RotateVaultKey<'info> {
+    #[account(mut, seeds = [VAULT_SEED, user.key().as_ref()], bump = vault.bump)]
+    pub vault: Account<'info, Vault>,
+    pub user: Signer<'info>,
+    /// CHECK: instructions sysvar, read by the Ed25519 authorizer
+    #[account(address = sysvar::instructions::ID)]
+    pub instructions: AccountInfo<'info>,
+}
+
 #[derive(Accounts)]
 pub struct GlobalTaxSwap<'info> {
     #[account(mut)] pub global_state: Account<'info, GlobalState>,
     #[account(mut)] pub user: Signer<'info>,
-    #[account(mut)] pub user_sol: Account<'info, TokenAccount>,
+    #[account(mut, token::mint = native_mint::ID)] pub user_sol: Account<'info, TokenAccount>,
+    pub meme_mint: Account<'info, Mint>,
+    #[account(seeds = [REGISTRY_ENTRY_SEED, meme_mint.key().as_ref()], bump = registry_entry.bump)]
+    pub registry_entry: Account<'info, RegistryEntry>,
     #[account(mut)] pub vault: Account<'info, Vault>,
     #[account(seeds = [VAULT_SEED, user.key().as_ref()], bump)] pub expected_vault: AccountInfo<'info>,
-    #[account(mut)] pub lp_vault: Account<'info, TokenAccount>,
-    #[account(mut)] pub treasury_vault: Account<'info, TokenAccount>,
-    #[account(mut)] pub rewards: Account<'info, RewardDistribution>,
-    #[account(mut)] pub badge_holders: Account<'info, BadgeHolders>,
+    #[account(mut, seeds = [LP_TAX_VAULT_SEED, meme_mint.key().as_ref()], bump, token::authority = global_state)]
+    pub lp_vault: Account<'info, TokenAccount>,
+    #[account(mut, seeds = [TREASURY_VAULT_SEED, meme_mint.key().as_ref()], bump, token::authority = global_state)]
+    pub treasury_vault: Account<'info, TokenAccount>,
+    #[account(mut, seeds = [REWARDS_VAULT_SEED], bump)]
+    pub rewards_vault: Account<'info, TokenAccount>,
+    #[account(mut, seeds = [REWARDS_SEED], bump = rewards.bump)] pub rewards: Account<'info, RewardDistribution>,
+    #[account(init_if_needed, payer = user, space = 8 + 32 + 8 + 8 + 1 + 1, seeds = [REWARD_ACCRUAL_SEED, user.key().as_ref(), &rewards.current_epoch.to_le_bytes()], bump)]
+    pub reward_accrual: Account<'info, RewardAccrual>,
+    #[account(init_if_needed, payer = user, space = 8 + 32 + 32 + 8 + 8 + 8 + 8 + 1, seeds = [BUY_COUNTER_SEED, meme_mint.key().as_ref(), user.key().as_ref()], bump)]
+    pub buy_counter: Account<'info, BuyCounter>,
+    #[account(mut, seeds = [BADGE_HOLDERS_SEED, meme_mint.key().as_ref()], bump = badge_holders.load()?.bump)]
+    pub badge_holders: AccountLoader<'info, BadgeHolders>,
+    #[account(seeds = [TAX_SCHEDULE_SEED], bump = tax_schedule.bump)]
+    pub tax_schedule: Account<'info, TaxSchedule>,
     #[account(init_if_needed, payer = user, space = 8 + 8 + 8 + 1, seeds = [b"velocity", &Clock::get()?.slot.to_le_bytes()], bump)]
     pub velocity: Account<'info, BlockSwapState>,
+    /// CHECK: instructions sysvar, read by the Ed25519 authorizer
+    #[account(address = sysvar::instructions::ID)]
+    pub instructions: AccountInfo<'info>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CrankRewards<'info> {
+    #[account(seeds = [b"global"], bump = global_state.bump)]
+    pub global_state: Account<'info, GlobalState>,
+    #[account(seeds = [REWARDS_SEED], bump = rewards.bump)]
+    pub rewards: Account<'info, RewardDistribution>,
+    #[account(mut, seeds = [BADGE_HOLDERS_SEED, badge_holders.load()?.meme_mint.as_ref()], bump = badge_holders.load()?.bump)]
+    pub badge_holders: AccountLoader<'info, BadgeHolders>,
+    #[account(seeds = [BADGE_HOLDER_PAGE_SEED, badge_holders.load()?.meme_mint.as_ref(), &badge_holders.load()?.crank_page().to_le_bytes()], bump = badge_holder_page.load()?.bump)]
+    pub badge_holder_page: AccountLoader<'info, BadgeHolderPage>,
+    #[account(mut, seeds = [REWARDS_VAULT_SEED], bump)]
+    pub rewards_vault: Account<'info, TokenAccount>,
+    #[account(mut, token::mint = native_mint::ID, token::authority = keeper)]
+    pub keeper_wsol: Account<'info, TokenAccount>,
+    pub keeper: Signer<'info>,
     pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct CloseRewardEpoch<'info> {
+    #[account(mut, seeds = [REWARDS_SEED], bump = rewards.bump)] pub rewards: Account<'info, RewardDistribution>,
+    #[account(init, payer = payer, space = 8 + 8 + 8 + 8 + 8 + 8 + 8 + 8 + 8 + 1, seeds = [REWARD_EPOCH_SEED, &rewards.current_epoch.to_le_bytes()], bump)]
+    pub reward_epoch: Account<'info, RewardEpoch>,
+    #[account(mut)] pub payer: Signer<'info>,
     pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-pub struct DistributeRewards<'info> {
-    #[account(mut)] pub rewards: Account<'info, RewardDistribution>,
-    #[account(mut)] pub badge_holders: Account<'info, BadgeHolders>,
-    #[account(mut)] pub treasury_vault: Account<'info, TokenAccount>,
+pub struct SetKeeperFee<'info> {
+    #[account(mut, seeds = [b"global"], bump = global_state.bump)]
+    pub global_state: Account<'info, GlobalState>,
+    /// CHECK: admin key or AdminCouncil PDA, verified by require_admin
+    pub authority: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimBadgeRewards<'info> {
+    #[account(seeds = [b"global"], bump = global_state.bump)]
+    pub global_state: Account<'info, GlobalState>,
+    #[account(seeds = [BADGE_HOLDERS_SEED, badge_record.meme_mint.as_ref()], bump = badge_holders.load()?.bump)]
+    pub badge_holders: AccountLoader<'info, BadgeHolders>,
+    #[account(mut, seeds = [BADGE_RECORD_SEED, badge_mint.key().as_ref()], bump = badge_record.bump)]
+    pub badge_record: Account<'info, BadgeRecord>,
+    pub badge_mint: InterfaceAccount<'info, token_interface::Mint>,
+    // SPL or Token-2022 badge; the earner must still hold it
+    #[account(token::mint = badge_mint, token::authority = user, constraint = user_badge_ata.amount == 1 @ SafePumpError::NotBadgeHolder)]
+    pub user_badge_ata: InterfaceAccount<'info, token_interface::TokenAccount>,
+    #[account(mut, seeds = [REWARDS_VAULT_SEED], bump)]
+    pub rewards_vault: Account<'info, TokenAccount>,
+    #[account(mut, token::mint = native_mint::ID, token::authority = user)]
+    pub user_wsol: Account<'info, TokenAccount>,
+    pub user: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+#[instruction(epoch: u64)]
+pub struct ClaimSwapperRewards<'info> {
+    #[account(seeds = [b"global"], bump = global_state.bump)]
+    pub global_state: Account<'info, GlobalState>,
+    #[account(mut, seeds = [REWARD_ACCRUAL_SEED, user.key().as_ref(), &epoch.to_le_bytes()], bump = reward_accrual.bump)]
+    pub reward_accrual: Account<'info, RewardAccrual>,
+    /// Only exists once `epoch` has been closed by close_reward_epoch.
+    #[account(mut, seeds = [REWARD_EPOCH_SEED, &epoch.to_le_bytes()], bump = reward_epoch.bump)]
+    pub reward_epoch: Account<'info, RewardEpoch>,
+    #[account(mut, seeds = [REWARDS_VAULT_SEED], bump)]
+    pub rewards_vault: Account<'info, TokenAccount>,
+    #[account(mut, token::mint = native_mint::ID, token::authority = user)]
+    pub user_wsol: Account<'info, TokenAccount>,
+    pub user: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeRewardsVault<'info> {
+    #[account(seeds = [b"global"], bump = global_state.bump)]
+    pub global_state: Account<'info, GlobalState>,
+    #[account(init, payer = payer, seeds = [REWARDS_VAULT_SEED], bump, token::mint = wsol_mint, token::authority = global_state)]
+    pub rewards_vault: Account<'info, TokenAccount>,
+    #[account(address = native_mint::ID)]
+    pub wsol_mint: Account<'info, Mint>,
+    /// CHECK: admin key or AdminCouncil PDA, verified by require_admin
+    pub authority: AccountInfo<'info>,
+    #[account(mut)] pub payer: Signer<'info>,
+    pub token_program: Program<'info, Token>,
     pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
 }
 
 #[derive(Accounts)]
 pub struct MintBadge<'info> {
     #[account(mut)] pub user: Signer<'info>,
-    #[account(init, payer = user, mint::decimals = 0, mint::authority = contract, seeds = [b"badge", mint.key().as_ref(), user.key().as_ref()], bump)]
+    #[account(seeds = [BUY_COUNTER_SEED, mint.key().as_ref(), user.key().as_ref()], bump = buy_counter.bump)]
+    pub buy_counter: Account<'info, BuyCounter>,
+    #[account(init, payer = user, mint::decimals = 0, mint::authority = contract, mint::freeze_authority = contract, seeds = [BADGE_MINT_SEED, mint.key().as_ref(), user.key().as_ref(), buy_counter.badges_revoked.to_le_bytes().as_ref()], bump)]
     pub badge_mint: Account<'info, Mint>,
     #[account(init_if_needed, payer = user, associated_token::mint = badge_mint, associated_token::authority = user)]
     pub user_badge_ata: Account<'info, TokenAccount>,
-    #[account(mut)] pub badge_holders: Account<'info, BadgeHolders>,
-    #[account(seeds = [b"contract", deployer.key().as_ref()], bump)]
+    #[account(seeds = [BADGE_CONFIG_SEED], bump = badge_config.bump)]
+    pub badge_config: Account<'info, BadgeConfig>,
+    #[account(mut, seeds = [BADGE_HOLDERS_SEED, mint.key().as_ref()], bump = badge_holders.load()?.bump)]
+    pub badge_holders: AccountLoader<'info, BadgeHolders>,
+    #[account(init_if_needed, payer = user, space = 8 + 32 + 8 + 1 + 7 + 32 * BADGE_HOLDER_PAGE_SIZE, seeds = [BADGE_HOLDER_PAGE_SEED, mint.key().as_ref(), &badge_holders.load()?.next_page().to_le_bytes()], bump)]
+    pub badge_holder_page: AccountLoader<'info, BadgeHolderPage>,
+    #[account(init, payer = user, space = 8 + 32 + 32 + 32 + 16 + 8 + 1 + 1 + 8 + 1, seeds = [BADGE_RECORD_SEED, badge_mint.key().as_ref()], bump)]
+    pub badge_record: Account<'info, BadgeRecord>,
+    #[account(mut, seeds = [b"contract", deployer.key().as_ref()], bump)]
     pub contract: Account<'info, TokenContract>,
     #[account(mut)] pub mint: Account<'info, Mint>,
+    /// CHECK: only used to derive the contract PDA
+    pub deployer: AccountInfo<'info>,
+
+    // Metaplex print edition
+    #[account(mut, seeds = [BADGE_EDITION_SEED, mint.key().as_ref()], bump)]
+    pub master_mint: Account<'info, Mint>,
+    #[account(mut, associated_token::mint = master_mint, associated_token::authority = contract)]
+    pub master_token: Account<'info, TokenAccount>,
+    /// CHECK: master badge metadata, verified by the metadata program
+    #[account(mut, seeds = [b"metadata", token_metadata_program.key().as_ref(), master_mint.key().as_ref()], bump, seeds::program = token_metadata_program.key())]
+    pub master_metadata: AccountInfo<'info>,
+    /// CHECK: master edition PDA, verified by the metadata program
+    #[account(mut, seeds = [b"metadata", token_metadata_program.key().as_ref(), master_mint.key().as_ref(), b"edition"], bump, seeds::program = token_metadata_program.key())]
+    pub master_edition: AccountInfo<'info>,
+    /// CHECK: edition marker PDA, derived and checked by the metadata program
+    #[account(mut)]
+    pub edition_mark: AccountInfo<'info>,
+    /// CHECK: created by the metadata program
+    #[account(mut, seeds = [b"metadata", token_metadata_program.key().as_ref(), badge_mint.key().as_ref()], bump, seeds::program = token_metadata_program.key())]
+    pub badge_metadata: AccountInfo<'info>,
+    /// CHECK: created by the metadata program
+    #[account(mut, seeds = [b"metadata", token_metadata_program.key().as_ref(), badge_mint.key().as_ref(), b"edition"], bump, seeds::program = token_metadata_program.key())]
+    pub badge_edition: AccountInfo<'info>,
+    pub token_metadata_program: Program<'info, Metadata>,
+
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+pub struct RevokeInactiveBadge<'info> {
+    #[account(seeds = [BADGE_CONFIG_SEED], bump = badge_config.bump)]
+    pub badge_config: Account<'info, BadgeConfig>,
+    #[account(mut, close = earner)]
+    pub badge_record: Account<'info, BadgeRecord>,
+    #[account(mut, seeds = [BUY_COUNTER_SEED, badge_record.meme_mint.as_ref(), badge_record.owner.as_ref()], bump = buy_counter.bump)]
+    pub buy_counter: Account<'info, BuyCounter>,
+    #[account(mut, seeds = [BADGE_HOLDERS_SEED, badge_record.meme_mint.as_ref()], bump = badge_holders.load()?.bump)]
+    pub badge_holders: AccountLoader<'info, BadgeHolders>,
+    // The revoked holder's page, the page of the last holder settled this crank pass,
+    // and the page of the last holder; any of them may be the same account
+    #[account(mut, seeds = [BADGE_HOLDER_PAGE_SEED, badge_record.meme_mint.as_ref(), &holder_page.load()?.page.to_le_bytes()], bump = holder_page.load()?.bump)]
+    pub holder_page: AccountLoader<'info, BadgeHolderPage>,
+    #[account(mut, seeds = [BADGE_HOLDER_PAGE_SEED, badge_record.meme_mint.as_ref(), &badge_holders.load()?.settled_page().to_le_bytes()], bump = settled_page.load()?.bump)]
+    pub settled_page: AccountLoader<'info, BadgeHolderPage>,
+    #[account(mut, seeds = [BADGE_HOLDER_PAGE_SEED, badge_record.meme_mint.as_ref(), &badge_holders.load()?.last_page().to_le_bytes()], bump = last_page.load()?.bump)]
+    pub last_page: AccountLoader<'info, BadgeHolderPage>,
+    /// CHECK: receives the record's rent
+    #[account(mut, address = badge_record.owner)]
+    pub earner: AccountInfo<'info>,
+    #[account(seeds = [b"global"], bump = global_state.bump)]
+    pub global_state: Account<'info, GlobalState>,
+    #[account(mut, seeds = [REWARDS_VAULT_SEED], bump)]
+    pub rewards_vault: Account<'info, TokenAccount>,
+    #[account(mut, seeds = [TREASURY_VAULT_SEED, badge_record.meme_mint.as_ref()], bump, token::authority = global_state)]
+    pub treasury_vault: Account<'info, TokenAccount>,
+    /// CHECK: the revoked badge's mint, only touched when it is soulbound
+    #[account(address = badge_record.badge_mint)]
+    pub badge_mint: AccountInfo<'info>,
+    /// CHECK: the earner's Token-2022 ATA for a soulbound badge, checked in the handler
+    #[account(mut)]
+    pub earner_badge_ata: AccountInfo<'info>,
+    // freeze authority of soulbound badge mints
+    #[account(seeds = [b"contract", deployer.key().as_ref()], bump)]
+    pub contract: Account<'info, TokenContract>,
+    /// CHECK: only used to derive the contract PDA
+    pub deployer: AccountInfo<'info>,
+    pub revoker: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+    pub token_2022_program: Program<'info, Token2022>,
+}
+
+#[derive(Accounts)]
+pub struct MintSoulboundBadge<'info> {
+    #[account(mut)] pub user: Signer<'info>,
+    #[account(seeds = [BUY_COUNTER_SEED, mint.key().as_ref(), user.key().as_ref()], bump = buy_counter.bump)]
+    pub buy_counter: Account<'info, BuyCounter>,
+    /// CHECK: created in the instruction as a NonTransferable Token-2022 mint
+    #[account(mut, seeds = [SOULBOUND_BADGE_SEED, mint.key().as_ref(), user.key().as_ref(), buy_counter.badges_revoked.to_le_bytes().as_ref()], bump)]
+    pub badge_mint: AccountInfo<'info>,
+    /// CHECK: user's Token-2022 ATA, created by the associated token program
+    #[account(mut)]
+    pub user_badge_ata: AccountInfo<'info>,
+    #[account(seeds = [BADGE_CONFIG_SEED], bump = badge_config.bump)]
+    pub badge_config: Account<'info, BadgeConfig>,
+    #[account(mut, seeds = [BADGE_HOLDERS_SEED, mint.key().as_ref()], bump = badge_holders.load()?.bump)]
+    pub badge_holders: AccountLoader<'info, BadgeHolders>,
+    #[account(init_if_needed, payer = user, space = 8 + 32 + 8 + 1 + 7 + 32 * BADGE_HOLDER_PAGE_SIZE, seeds = [BADGE_HOLDER_PAGE_SEED, mint.key().as_ref(), &badge_holders.load()?.next_page().to_le_bytes()], bump)]
+    pub badge_holder_page: AccountLoader<'info, BadgeHolderPage>,
+    #[account(init, payer = user, space = 8 + 32 + 32 + 32 + 16 + 8 + 1 + 1 + 8 + 1, seeds = [BADGE_RECORD_SEED, badge_mint.key().as_ref()], bump)]
+    pub badge_record: Account<'info, BadgeRecord>,
+    #[account(seeds = [b"contract", deployer.key().as_ref()], bump)]
+    pub contract: Account<'info, TokenContract>,
+    pub mint: Account<'info, Mint>,
+    /// CHECK: only used to derive the contract PDA
+    pub deployer: AccountInfo<'info>,
+    pub token_program: Program<'info, Token2022>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeBadgeEdition<'info> {
+    // contracts created before the space fix are too short to hold both badge keys
+    #[account(mut, seeds = [b"contract", deployer.key().as_ref()], bump, constraint = contract.badge_mint.is_none() @ SafePumpError::AlreadyInitialized, realloc = 8 + TokenContract::INIT_SPACE, realloc::payer = deployer, realloc::zero = false)]
+    pub contract: Account<'info, TokenContract>,
     #[account(mut)] pub deployer: Signer<'info>,
+    pub mint: Account<'info, Mint>,
+    #[account(seeds = [b"metadata", token_metadata_program.key().as_ref(), mint.key().as_ref()], bump, seeds::program = token_metadata_program.key())]
+    pub meme_metadata: Account<'info, MetadataAccount>,
+    #[account(init, payer = deployer, mint::decimals = 0, mint::authority = contract, mint::freeze_authority = contract, seeds = [BADGE_EDITION_SEED, mint.key().as_ref()], bump)]
+    pub master_mint: Account<'info, Mint>,
+    #[account(init, payer = deployer, associated_token::mint = master_mint, associated_token::authority = contract)]
+    pub master_token: Account<'info, TokenAccount>,
+    /// CHECK: created by the metadata program
+    #[account(mut, seeds = [b"metadata", token_metadata_program.key().as_ref(), master_mint.key().as_ref()], bump, seeds::program = token_metadata_program.key())]
+    pub master_metadata: AccountInfo<'info>,
+    /// CHECK: created by the metadata program
+    #[account(mut, seeds = [b"metadata", token_metadata_program.key().as_ref(), master_mint.key().as_ref(), b"edition"], bump, seeds::program = token_metadata_program.key())]
+    pub master_edition: AccountInfo<'info>,
+    pub token_metadata_program: Program<'info, Metadata>,
     pub token_program: Program<'info, Token>,
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
 }
 
 #[derive(Accounts)]
-pub struct WithdrawTreasury<'info> {
-    #[account(mut)] pub global_state: Account<'info, GlobalState>,
-    #[account(mut)] pub treasury_vault: Account<'info, TokenAccount>,
-    #[account(mut)] pub destination: AccountInfo<'info>,
-    #[account(mut)] pub signer: Signer<'info>,
+pub struct InitializeVaults<'info> {
+    #[account(seeds = [b"global"], bump = global_state.bump)]
+    pub global_state: Account<'info, GlobalState>,
+    pub meme_mint: Account<'info, Mint>,
+    #[account(seeds = [REGISTRY_ENTRY_SEED, meme_mint.key().as_ref()], bump = registry_entry.bump)]
+    pub registry_entry: Account<'info, RegistryEntry>,
+    #[account(init, payer = payer, seeds = [TREASURY_VAULT_SEED, meme_mint.key().as_ref()], bump, token::mint = wsol_mint, token::authority = global_state)]
+    pub treasury_vault: Account<'info, TokenAccount>,
+    #[account(init, payer = payer, seeds = [LP_TAX_VAULT_SEED, meme_mint.key().as_ref()], bump, token::mint = wsol_mint, token::authority = global_state)]
+    pub lp_vault: Account<'info, TokenAccount>,
+    #[account(address = native_mint::ID)]
+    pub wsol_mint: Account<'info, Mint>,
+    #[account(mut)] pub payer: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+pub struct SetWithdrawalLimits<'info> {
+    #[account(mut, seeds = [b"global"], bump = global_state.bump)]
+    pub global_state: Account<'info, GlobalState>,
+    /// CHECK: admin key or AdminCouncil PDA, verified by require_admin
+    pub authority: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetGuardian<'info> {
+    #[account(mut, seeds = [b"global"], bump = global_state.bump)]
+    pub global_state: Account<'info, GlobalState>,
+    /// CHECK: admin, current guardian, or AdminCouncil PDA, verified in the handler
+    pub authority: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct QueueWithdrawal<'info> {
+    #[account(mut, seeds = [b"global"], bump = global_state.bump)]
+    pub global_state: Account<'info, GlobalState>,
+    #[account(init, payer = payer, space = 8 + 8 + 32 + 8 + 32 + 8 + 8 + 32 + 1, seeds = [WITHDRAWAL_SEED, &global_state.next_withdrawal_id.to_le_bytes()], bump)]
+    pub withdrawal: Account<'info, WithdrawalRequest>,
+    /// CHECK: admin key or AdminCouncil PDA, verified by require_admin
+    pub authority: AccountInfo<'info>,
+    #[account(mut)] pub payer: Signer<'info>,
     pub system_program: Program<'info, System>,
 }
 
+#[derive(Accounts)]
+pub struct ExecuteWithdrawal<'info> {
+    #[account(mut, seeds = [b"global"], bump = global_state.bump)]
+    pub global_state: Account<'info, GlobalState>,
+    #[account(mut, close = payer, seeds = [WITHDRAWAL_SEED, &withdrawal.id.to_le_bytes()], bump = withdrawal.bump)]
+    pub withdrawal: Account<'info, WithdrawalRequest>,
+    #[account(mut, seeds = [TREASURY_VAULT_SEED, withdrawal.meme_mint.as_ref()], bump, token::authority = global_state)]
+    pub treasury_vault: Account<'info, TokenAccount>,
+    // WSOL token account fixed at queue time
+    #[account(mut, address = withdrawal.destination, token::mint = native_mint::ID)]
+    pub destination: Account<'info, TokenAccount>,
+    /// CHECK: rent refund for whoever queued the request
+    #[account(mut, address = withdrawal.payer)]
+    pub payer: AccountInfo<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct CancelWithdrawal<'info> {
+    #[account(seeds = [b"global"], bump = global_state.bump)]
+    pub global_state: Account<'info, GlobalState>,
+    #[account(mut, close = payer, seeds = [WITHDRAWAL_SEED, &withdrawal.id.to_le_bytes()], bump = withdrawal.bump)]
+    pub withdrawal: Account<'info, WithdrawalRequest>,
+    /// CHECK: rent refund for whoever queued the request
+    #[account(mut, address = withdrawal.payer)]
+    pub payer: AccountInfo<'info>,
+    /// CHECK: admin, guardian, or AdminCouncil PDA, verified in the handler
+    pub authority: AccountInfo<'info>,
+}
+
 #[derive(Accounts)]
 pub struct InitializeContract<'info> {
-    #[account(init, payer = deployer, space = 8 + 1 + 8 + 32 + 8 + 8 + 8 + 8 + 1 + 8 + 1 + 1 + 1 + (4 + 32*4) + (4 + 8*4) + 8 + 33 + 33, seeds = [b"contract", deployer.key().as_ref()], bump)]
+    #[account(init, payer = deployer, space = 8 + TokenContract::INIT_SPACE, seeds = [b"contract", deployer.key().as_ref()], bump)]
     pub contract: Account<'info, TokenContract>,
     #[account(mut)] pub deployer: Signer<'info>,
     #[account(mut)] pub mint: Account<'info, Mint>,
@@ -841,6 +2932,7 @@ pub struct ChildSwap<'info> {
     pub user: Signer<'info>,
     #[account(mut)]
     pub clock: Sysvar<'info, Clock>,
+    #[account(mut)]
     pub user_sol: Account<'info, TokenAccount>,
     #[account(mut)]
     pub user_token: Account<'info, TokenAccount>,
@@ -862,26 +2954,42 @@ pub struct ChildSwap<'info> {
     // Mothership global state
     #[account(mut, address = MOTHERSHIP_PROGRAM_ID_PUBKEY)]
     pub mothership_program: Program<'info, crate::program::SafePump>,
-    #[account(mut)]
+    #[account(mut, seeds = [b"global"], bump = global_state.bump)]
     pub global_state: Account<'info, GlobalState>,
-    #[account(mut)]
+    #[account(mut, seeds = [LP_TAX_VAULT_SEED, mint.key().as_ref()], bump, token::authority = global_state)]
     pub lp_vault: Account<'info, TokenAccount>,
-    #[account(mut)]
+    #[account(mut, seeds = [TREASURY_VAULT_SEED, mint.key().as_ref()], bump, token::authority = global_state)]
     pub treasury_vault: Account<'info, TokenAccount>,
-    #[account(mut)]
+    #[account(mut, seeds = [REWARDS_VAULT_SEED], bump)]
+    pub rewards_vault: Account<'info, TokenAccount>,
+    #[account(mut, seeds = [REWARDS_SEED], bump = rewards.bump)]
     pub rewards: Account<'info, RewardDistribution>,
-    #[account(mut)]
-    pub badge_holders: Account<'info, BadgeHolders>,
+    /// CHECK: created on the user's first swap by global_tax_swap
+    #[account(mut, seeds = [REWARD_ACCRUAL_SEED, user.key().as_ref(), &rewards.current_epoch.to_le_bytes()], bump)]
+    pub reward_accrual: AccountInfo<'info>,
+    /// CHECK: created on the user's first buy by global_tax_swap
+    #[account(mut, seeds = [BUY_COUNTER_SEED, mint.key().as_ref(), user.key().as_ref()], bump)]
+    pub buy_counter: AccountInfo<'info>,
+    #[account(mut, seeds = [BADGE_HOLDERS_SEED, mint.key().as_ref()], bump = badge_holders.load()?.bump)]
+    pub badge_holders: AccountLoader<'info, BadgeHolders>,
+    #[account(seeds = [TAX_SCHEDULE_SEED], bump = tax_schedule.bump)]
+    pub tax_schedule: Account<'info, TaxSchedule>,
+    #[account(seeds = [REGISTRY_ENTRY_SEED, mint.key().as_ref()], bump = registry_entry.bump)]
+    pub registry_entry: Account<'info, RegistryEntry>,
     #[account(mut)]
     pub vault: Account<'info, Vault>,
     #[account(seeds = [VAULT_SEED, user.key().as_ref()], bump)]
     pub expected_vault: AccountInfo<'info>,
-    #[account(init_if_needed, payer = user, space = 8 + 8 + 8 + 1, seeds = [b"velocity", &clock.slot.to_le_bytes()], bump)]
-    pub velocity: Account<'info, BlockSwapState>,
+    /// CHECK: created for the slot by global_tax_swap
+    #[account(mut, seeds = [b"velocity", &clock.slot.to_le_bytes()], bump)]
+    pub velocity: AccountInfo<'info>,
 
     #[account(mut)]
     pub user_state: Account<'info, UserSwapData>,
 
+    /// CHECK: instructions sysvar, read by the Ed25519 authorizer
+    #[account(address = sysvar::instructions::ID)]
+    pub instructions: AccountInfo<'info>,
     pub token_program: Program<'info, Token>,
     pub system_program: Program<'info, System>,
     pub clock: Sysvar<'info, Clock>,
@@ -889,3 +2997,4 @@ pub struct ChildSwap<'info> {
 
 pub mod stealth_airdrop_vault;
 use stealth_airdrop_vault::*;
+pub mod zk_proof;
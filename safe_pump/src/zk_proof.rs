@@ -0,0 +1,82 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::sysvar::instructions::get_instruction_relative;
+use bytemuck::Pod;
+use solana_zk_sdk::zk_elgamal_proof_program::{
+    self,
+    instruction::ProofInstruction,
+    proof_data::{ProofType, ZkProofData},
+    state::ProofContextState,
+};
+
+use crate::SafePumpError;
+
+/// Where the caller says the proof for this instruction was verified.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ProofLocation {
+    /// Verify instruction `offset` positions away in the same transaction. It must
+    /// write a context state with the caller as `context_state_authority`; the
+    /// proof data alone is public and could be lifted from anyone's transaction.
+    InstructionOffset(i64),
+    /// Context state account written by an earlier verify instruction, passed in
+    /// as the proof account. Its `context_state_authority` must be the caller, so
+    /// nobody can present a context someone else paid to verify.
+    ContextStateAccount,
+}
+
+/// Returns the context of a proof the ZK ElGamal proof program has verified.
+/// Rejects proofs not produced by the real program or of the wrong type; the
+/// program only writes a context, or lets its verify instruction succeed, once
+/// `verify_proof` has passed.
+pub fn verify_and_extract_context<T, U>(
+    location: ProofLocation,
+    proof_account: &AccountInfo,
+    context_state_authority: &Pubkey,
+) -> Result<U>
+where
+    T: Pod + ZkProofData<U>,
+    U: Pod,
+{
+    match location {
+        ProofLocation::InstructionOffset(offset) => {
+            // `proof_account` is the instructions sysvar here
+            let ix = get_instruction_relative(offset, proof_account)?;
+            require_keys_eq!(ix.program_id, zk_elgamal_proof_program::id(), SafePumpError::ZkProofProgramMismatch);
+            require!(
+                ProofInstruction::instruction_type(&ix.data) == Some(verify_instruction_for(T::PROOF_TYPE)?),
+                SafePumpError::ZkProofTypeMismatch
+            );
+            // Proof in instruction data with a context: [context state, context state authority]
+            let authority = ix.accounts.get(1).ok_or(SafePumpError::ZkProofAuthorityMismatch)?;
+            require_keys_eq!(authority.pubkey, *context_state_authority, SafePumpError::ZkProofAuthorityMismatch);
+            let proof = ProofInstruction::proof_data::<T, U>(&ix.data).ok_or(SafePumpError::InvalidZkProofContext)?;
+            Ok(*proof.context_data())
+        }
+        ProofLocation::ContextStateAccount => {
+            require_keys_eq!(*proof_account.owner, zk_elgamal_proof_program::id(), SafePumpError::ZkProofProgramMismatch);
+            let data = proof_account.try_borrow_data()?;
+            let state = bytemuck::try_from_bytes::<ProofContextState<U>>(&data)
+                .map_err(|_| error!(SafePumpError::InvalidZkProofContext))?;
+            require!(state.proof_type == T::PROOF_TYPE.into(), SafePumpError::ZkProofTypeMismatch);
+            require_keys_eq!(state.context_state_authority, *context_state_authority, SafePumpError::ZkProofAuthorityMismatch);
+            Ok(state.proof_context)
+        }
+    }
+}
+
+fn verify_instruction_for(proof_type: ProofType) -> Result<ProofInstruction> {
+    Ok(match proof_type {
+        ProofType::ZeroCiphertext => ProofInstruction::VerifyZeroCiphertext,
+        ProofType::CiphertextCiphertextEquality => ProofInstruction::VerifyCiphertextCiphertextEquality,
+        ProofType::CiphertextCommitmentEquality => ProofInstruction::VerifyCiphertextCommitmentEquality,
+        ProofType::PubkeyValidity => ProofInstruction::VerifyPubkeyValidity,
+        ProofType::PercentageWithCap => ProofInstruction::VerifyPercentageWithCap,
+        ProofType::BatchedRangeProofU64 => ProofInstruction::VerifyBatchedRangeProofU64,
+        ProofType::BatchedRangeProofU128 => ProofInstruction::VerifyBatchedRangeProofU128,
+        ProofType::BatchedRangeProofU256 => ProofInstruction::VerifyBatchedRangeProofU256,
+        ProofType::GroupedCiphertext2HandlesValidity => ProofInstruction::VerifyGroupedCiphertext2HandlesValidity,
+        ProofType::BatchedGroupedCiphertext2HandlesValidity => ProofInstruction::VerifyBatchedGroupedCiphertext2HandlesValidity,
+        ProofType::GroupedCiphertext3HandlesValidity => ProofInstruction::VerifyGroupedCiphertext3HandlesValidity,
+        ProofType::BatchedGroupedCiphertext3HandlesValidity => ProofInstruction::VerifyBatchedGroupedCiphertext3HandlesValidity,
+        ProofType::Uninitialized => return err!(SafePumpError::ZkProofTypeMismatch),
+    })
+}
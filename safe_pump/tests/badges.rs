@@ -0,0 +1,717 @@
+// Buy counters vs badge holders, and badge NFTs printed through the metadata program
+// stand-in in common/metadata_program.rs.
+mod common;
+
+use anchor_lang::Space;
+use anchor_spl::{
+    associated_token::{self, get_associated_token_address, get_associated_token_address_with_program_id},
+    metadata::mpl_token_metadata::{
+        self,
+        accounts::{Edition, MasterEdition, Metadata},
+    },
+    token_2022::spl_token_2022::{
+        self,
+        error::TokenError,
+        extension::{BaseStateWithExtensions, ExtensionType, StateWithExtensions},
+    },
+};
+use common::{metadata_program::*, *};
+use safe_pump::{BadgeConfig, BadgeMinted, BadgeRecord, BadgeRevoked, BuyCounter, SafePumpError, REWARDS_VAULT_SEED, TokenContract, BADGE_CONFIG_SEED, BADGE_EDITION_SEED, BADGE_HOLDERS_SEED, BADGE_MINT_SEED, BADGE_RECORD_SEED, BUY_COUNTER_SEED, SOULBOUND_BADGE_SEED};
+use solana_program_test::{ProgramTest, ProgramTestContext};
+use solana_sdk::{
+    clock::Clock,
+    instruction::{AccountMeta, Instruction, InstructionError},
+    program_option::COption,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    rent::Rent,
+    signature::{Keypair, Signer},
+    sysvar,
+    transaction::TransactionError,
+};
+
+struct Badges {
+    coin: Coin,
+    deployer: Keypair,
+}
+
+impl Badges {
+    fn contract(&self) -> Pubkey {
+        contract_address(&self.deployer.pubkey())
+    }
+
+    fn master_mint(&self) -> Pubkey {
+        pda(&[BADGE_EDITION_SEED, self.coin.meme_mint.as_ref()])
+    }
+
+    fn badge_mint(&self, user: &Pubkey, badges_revoked: u64) -> Pubkey {
+        pda(&[BADGE_MINT_SEED, self.coin.meme_mint.as_ref(), user.as_ref(), &badges_revoked.to_le_bytes()])
+    }
+
+    fn soulbound_badge_mint(&self, user: &Pubkey, badges_revoked: u64) -> Pubkey {
+        pda(&[SOULBOUND_BADGE_SEED, self.coin.meme_mint.as_ref(), user.as_ref(), &badges_revoked.to_le_bytes()])
+    }
+}
+
+async fn start() -> (ProgramTestContext, Badges) {
+    let mut test: ProgramTest = program_test();
+    let coin = add_coin(&mut test);
+    let deployer = add_badge_setup(&mut test, &coin);
+    let mut ctx = test.start_with_context().await;
+    init_vaults(&mut ctx, &coin).await;
+    fund(&mut ctx, &deployer.pubkey()).await;
+    (ctx, Badges { coin, deployer })
+}
+
+fn initialize_badge_edition_ix(badges: &Badges, soulbound: bool) -> Instruction {
+    let master_mint = badges.master_mint();
+    ix(
+        safe_pump::accounts::InitializeBadgeEdition {
+            contract: badges.contract(),
+            deployer: badges.deployer.pubkey(),
+            mint: badges.coin.meme_mint,
+            meme_metadata: metadata_address(&badges.coin.meme_mint),
+            master_mint,
+            master_token: get_associated_token_address(&badges.contract(), &master_mint),
+            master_metadata: metadata_address(&master_mint),
+            master_edition: edition_address(&master_mint),
+            token_metadata_program: mpl_token_metadata::ID,
+            token_program: spl_token::ID,
+            associated_token_program: associated_token::ID,
+            system_program: solana_sdk::system_program::ID,
+            rent: sysvar::rent::ID,
+        },
+        safe_pump::instruction::InitializeBadgeEdition { soulbound },
+    )
+}
+
+async fn mint_badge_ix(ctx: &mut ProgramTestContext, badges: &Badges, user: &Pubkey) -> Instruction {
+    let meme_mint = badges.coin.meme_mint;
+    let contract: TokenContract = anchor_state(ctx, badges.contract()).await;
+    let next_page = badge_holders(ctx, &meme_mint).await.next_page();
+    let badge_mint = badges.badge_mint(user, 0);
+    let master_mint = badges.master_mint();
+    ix(
+        safe_pump::accounts::MintBadge {
+            user: *user,
+            buy_counter: pda(&[BUY_COUNTER_SEED, meme_mint.as_ref(), user.as_ref()]),
+            badge_mint,
+            user_badge_ata: get_associated_token_address(user, &badge_mint),
+            badge_config: pda(&[BADGE_CONFIG_SEED]),
+            badge_holders: pda(&[BADGE_HOLDERS_SEED, meme_mint.as_ref()]),
+            badge_holder_page: badge_holder_page_address(&meme_mint, next_page),
+            badge_record: pda(&[BADGE_RECORD_SEED, badge_mint.as_ref()]),
+            contract: badges.contract(),
+            mint: meme_mint,
+            deployer: badges.deployer.pubkey(),
+            master_mint,
+            master_token: get_associated_token_address(&badges.contract(), &master_mint),
+            master_metadata: metadata_address(&master_mint),
+            master_edition: edition_address(&master_mint),
+            edition_mark: edition_marker_address(&master_mint, contract.badge_editions_printed + 1),
+            badge_metadata: metadata_address(&badge_mint),
+            badge_edition: edition_address(&badge_mint),
+            token_metadata_program: mpl_token_metadata::ID,
+            token_program: spl_token::ID,
+            associated_token_program: associated_token::ID,
+            system_program: solana_sdk::system_program::ID,
+            rent: sysvar::rent::ID,
+        },
+        safe_pump::instruction::MintBadge {},
+    )
+}
+
+/// `user` claims a soulbound badge against `earner`'s buy counter.
+async fn mint_soulbound_badge_ix(ctx: &mut ProgramTestContext, badges: &Badges, user: &Pubkey, earner: &Pubkey) -> Instruction {
+    let meme_mint = badges.coin.meme_mint;
+    let next_page = badge_holders(ctx, &meme_mint).await.next_page();
+    let badge_mint = badges.soulbound_badge_mint(user, 0);
+    ix(
+        safe_pump::accounts::MintSoulboundBadge {
+            user: *user,
+            buy_counter: pda(&[BUY_COUNTER_SEED, meme_mint.as_ref(), earner.as_ref()]),
+            badge_mint,
+            user_badge_ata: get_associated_token_address_with_program_id(user, &badge_mint, &spl_token_2022::ID),
+            badge_config: pda(&[BADGE_CONFIG_SEED]),
+            badge_holders: pda(&[BADGE_HOLDERS_SEED, meme_mint.as_ref()]),
+            badge_holder_page: badge_holder_page_address(&meme_mint, next_page),
+            badge_record: pda(&[BADGE_RECORD_SEED, badge_mint.as_ref()]),
+            contract: badges.contract(),
+            mint: meme_mint,
+            deployer: badges.deployer.pubkey(),
+            token_program: spl_token_2022::ID,
+            associated_token_program: associated_token::ID,
+            system_program: solana_sdk::system_program::ID,
+        },
+        safe_pump::instruction::MintSoulboundBadge {},
+    )
+}
+
+async fn account_data(ctx: &mut ProgramTestContext, address: Pubkey) -> Vec<u8> {
+    ctx.banks_client.get_account(address).await.unwrap().expect("account").data
+}
+
+#[tokio::test]
+async fn buys_only_touch_the_buy_counter() {
+    let (mut ctx, badges) = start().await;
+    let trader = new_trader(&mut ctx, 10 * LAMPORTS_PER_SOL).await;
+    tax_swap(&mut ctx, &badges.coin, &trader, 2 * LAMPORTS_PER_SOL, true).await.unwrap();
+    tax_swap(&mut ctx, &badges.coin, &trader, LAMPORTS_PER_SOL, false).await.unwrap();
+
+    let user = trader.user.pubkey();
+    let counter: BuyCounter = anchor_state(&mut ctx, pda(&[BUY_COUNTER_SEED, badges.coin.meme_mint.as_ref(), user.as_ref()])).await;
+    assert_eq!((counter.user, counter.meme_mint), (user, badges.coin.meme_mint));
+    // The sell leaves the counter alone
+    assert_eq!((counter.buy_swaps, counter.buy_volume), (1, 2 * LAMPORTS_PER_SOL));
+
+    // Eligible for a badge, but nobody is a holder until one is minted
+    let holders = badge_holders(&mut ctx, &badges.coin.meme_mint).await;
+    assert_eq!((holders.holder_count, holders.total_badge_weight), (0, 0));
+    let page = badge_holder_page(&mut ctx, &badges.coin.meme_mint, 0).await;
+    assert!(page.holders.iter().all(|h| *h == Pubkey::default()));
+}
+
+#[tokio::test]
+async fn badge_edition_is_an_unlimited_master_named_after_the_coin() {
+    let (mut ctx, badges) = start().await;
+    send(&mut ctx, &[initialize_badge_edition_ix(&badges, false)], &[&badges.deployer]).await.unwrap();
+
+    let master_mint = badges.master_mint();
+    let metadata = Metadata::safe_deserialize(&account_data(&mut ctx, metadata_address(&master_mint)).await).unwrap();
+    assert_eq!(metadata.name.trim_end_matches('\0'), "Seed Coin Badge");
+    assert_eq!(metadata.symbol.trim_end_matches('\0'), "BSEED");
+    assert_eq!(metadata.uri.trim_end_matches('\0'), MEME_URI);
+    assert_eq!(metadata.update_authority, badges.contract());
+
+    let edition = MasterEdition::safe_deserialize(&account_data(&mut ctx, edition_address(&master_mint)).await).unwrap();
+    assert_eq!((edition.supply, edition.max_supply), (0, None));
+    assert_eq!(token_balance(&mut ctx, get_associated_token_address(&badges.contract(), &master_mint)).await, 1);
+    let mint = spl_token::state::Mint::unpack(&account_data(&mut ctx, master_mint).await).unwrap();
+    assert_eq!(mint.mint_authority, COption::Some(edition_address(&master_mint)));
+
+    let contract: TokenContract = anchor_state(&mut ctx, badges.contract()).await;
+    assert_eq!(contract.badge_mint, Some(master_mint));
+    assert_eq!(contract.badge_master_edition, Some(edition_address(&master_mint)));
+}
+
+#[tokio::test]
+async fn badge_edition_grows_a_contract_created_with_the_old_space() {
+    let (mut ctx, badges) = start().await;
+    // the pre-InitSpace layout allotted 344 bytes, seven short of both badge keys
+    let mut contract = ctx.banks_client.get_account(badges.contract()).await.unwrap().unwrap();
+    contract.data.truncate(344);
+    contract.lamports = Rent::default().minimum_balance(344);
+    ctx.set_account(&badges.contract(), &contract.into());
+
+    send(&mut ctx, &[initialize_badge_edition_ix(&badges, false)], &[&badges.deployer]).await.unwrap();
+    assert_eq!(account_data(&mut ctx, badges.contract()).await.len(), 8 + TokenContract::INIT_SPACE);
+    let contract: TokenContract = anchor_state(&mut ctx, badges.contract()).await;
+    assert_eq!(contract.badge_mint, Some(badges.master_mint()));
+    assert_eq!(contract.badge_master_edition, Some(edition_address(&badges.master_mint())));
+    assert_eq!(contract.total_supply, 1_000_000_000);
+}
+
+#[tokio::test]
+async fn mint_badge_registers_the_holder_and_prints_an_edition() {
+    let (mut ctx, badges) = start().await;
+    send(&mut ctx, &[initialize_badge_edition_ix(&badges, false)], &[&badges.deployer]).await.unwrap();
+    let master_edition = edition_address(&badges.master_mint());
+
+    for (i, edition) in [1u64, 2].into_iter().enumerate() {
+        let trader = new_trader(&mut ctx, 10 * LAMPORTS_PER_SOL).await;
+        let user = trader.user.pubkey();
+        tax_swap(&mut ctx, &badges.coin, &trader, LAMPORTS_PER_SOL, true).await.unwrap();
+        let mint_badge = mint_badge_ix(&mut ctx, &badges, &user).await;
+        let logs = send_with_logs(&mut ctx, &[mint_badge], &[&trader.user]).await.unwrap();
+
+        let badge_mint = badges.badge_mint(&user, 0);
+        let minted = events::<BadgeMinted>(&logs);
+        assert_eq!(minted.len(), 1);
+        assert_eq!((minted[0].user, minted[0].badge_mint, minted[0].soulbound, minted[0].tier), (user, badge_mint, false, 0));
+
+        let holders = badge_holders(&mut ctx, &badges.coin.meme_mint).await;
+        assert_eq!(holders.holder_count, i as u64 + 1);
+        assert_eq!(badge_holder_page(&mut ctx, &badges.coin.meme_mint, 0).await.holders[i], user);
+        assert_eq!(holders.total_badge_weight, i as u64 + 1);
+
+        let record: BadgeRecord = anchor_state(&mut ctx, pda(&[BADGE_RECORD_SEED, badge_mint.as_ref()])).await;
+        assert_eq!((record.badge_mint, record.owner, record.weight), (badge_mint, user, 1));
+
+        assert_eq!(token_balance(&mut ctx, get_associated_token_address(&user, &badge_mint)).await, 1);
+        let print = Edition::from_bytes(&account_data(&mut ctx, edition_address(&badge_mint)).await).unwrap();
+        assert_eq!((print.parent, print.edition), (master_edition, edition));
+        let metadata = Metadata::safe_deserialize(&account_data(&mut ctx, metadata_address(&badge_mint)).await).unwrap();
+        assert_eq!((metadata.mint, metadata.name.trim_end_matches('\0')), (badge_mint, "Seed Coin Badge"));
+    }
+
+    let master = MasterEdition::safe_deserialize(&account_data(&mut ctx, master_edition).await).unwrap();
+    assert_eq!(master.supply, 2);
+}
+
+/// Pretends `count` weight-1 holders already minted, without writing their pages.
+async fn set_holder_count(ctx: &mut ProgramTestContext, badges: &Badges, count: u64) {
+    let mut holders = badge_holders(ctx, &badges.coin.meme_mint).await;
+    holders.holder_count = count;
+    holders.total_badge_weight = count;
+    ctx.set_account(&pda(&[BADGE_HOLDERS_SEED, badges.coin.meme_mint.as_ref()]), &zero_copy_account(&holders).into());
+}
+
+#[tokio::test]
+async fn mint_badge_opens_the_next_holder_page() {
+    let (mut ctx, badges) = start().await;
+    send(&mut ctx, &[initialize_badge_edition_ix(&badges, false)], &[&badges.deployer]).await.unwrap();
+    set_holder_count(&mut ctx, &badges, 256).await;
+    let trader = new_trader(&mut ctx, 10 * LAMPORTS_PER_SOL).await;
+    tax_swap(&mut ctx, &badges.coin, &trader, LAMPORTS_PER_SOL, true).await.unwrap();
+
+    let mint_badge = mint_badge_ix(&mut ctx, &badges, &trader.user.pubkey()).await;
+    send(&mut ctx, &[mint_badge], &[&trader.user]).await.unwrap();
+
+    let holders = badge_holders(&mut ctx, &badges.coin.meme_mint).await;
+    assert_eq!(holders.holder_count, 257);
+    let page = badge_holder_page(&mut ctx, &badges.coin.meme_mint, 1).await;
+    assert_eq!((page.meme_mint, page.page), (badges.coin.meme_mint, 1));
+    assert_eq!(page.holders[0], trader.user.pubkey());
+}
+
+#[tokio::test]
+async fn mint_badge_stops_at_a_thousand_holders() {
+    let (mut ctx, badges) = start().await;
+    send(&mut ctx, &[initialize_badge_edition_ix(&badges, false)], &[&badges.deployer]).await.unwrap();
+    set_holder_count(&mut ctx, &badges, 1000).await;
+    let trader = new_trader(&mut ctx, 10 * LAMPORTS_PER_SOL).await;
+    tax_swap(&mut ctx, &badges.coin, &trader, LAMPORTS_PER_SOL, true).await.unwrap();
+
+    let mint_badge = mint_badge_ix(&mut ctx, &badges, &trader.user.pubkey()).await;
+    let result = send(&mut ctx, &[mint_badge], &[&trader.user]).await;
+    assert_custom_error(result, 0, SafePumpError::BadgeHolderLimitReached.into());
+}
+
+#[tokio::test]
+async fn mint_badge_rejects_a_counter_below_the_lowest_tier() {
+    let (mut ctx, badges) = start().await;
+    send(&mut ctx, &[initialize_badge_edition_ix(&badges, false)], &[&badges.deployer]).await.unwrap();
+    let trader = new_trader(&mut ctx, 10 * LAMPORTS_PER_SOL).await;
+    tax_swap(&mut ctx, &badges.coin, &trader, LAMPORTS_PER_SOL / 2, true).await.unwrap();
+
+    let mint_badge = mint_badge_ix(&mut ctx, &badges, &trader.user.pubkey()).await;
+    let result = send_with_logs(&mut ctx, &[mint_badge], &[&trader.user]).await;
+    let code: u32 = SafePumpError::InsufficientBuySwaps.into();
+    assert_eq!(result.unwrap_err(), TransactionError::InstructionError(0, InstructionError::Custom(code)));
+    let holders = badge_holders(&mut ctx, &badges.coin.meme_mint).await;
+    assert_eq!(holders.holder_count, 0);
+}
+
+#[tokio::test]
+async fn mint_badge_needs_the_badge_edition() {
+    let (mut ctx, badges) = start().await;
+    let trader = new_trader(&mut ctx, 10 * LAMPORTS_PER_SOL).await;
+    tax_swap(&mut ctx, &badges.coin, &trader, LAMPORTS_PER_SOL, true).await.unwrap();
+
+    // Without initialize_badge_edition the master mint doesn't exist yet
+    let mint_badge = mint_badge_ix(&mut ctx, &badges, &trader.user.pubkey()).await;
+    assert!(send_with_logs(&mut ctx, &[mint_badge], &[&trader.user]).await.is_err());
+}
+
+#[tokio::test]
+async fn soulbound_badge_is_a_non_transferable_token_2022_mint() {
+    let (mut ctx, badges) = start().await;
+    send(&mut ctx, &[initialize_badge_edition_ix(&badges, true)], &[&badges.deployer]).await.unwrap();
+    let trader = new_trader(&mut ctx, 10 * LAMPORTS_PER_SOL).await;
+    let user = trader.user.pubkey();
+    tax_swap(&mut ctx, &badges.coin, &trader, LAMPORTS_PER_SOL, true).await.unwrap();
+
+    let mint_badge = mint_soulbound_badge_ix(&mut ctx, &badges, &user, &user).await;
+    let logs = send_with_logs(&mut ctx, &[mint_badge], &[&trader.user]).await.unwrap();
+
+    let badge_mint = badges.soulbound_badge_mint(&user, 0);
+    let minted = events::<BadgeMinted>(&logs);
+    assert_eq!((minted[0].user, minted[0].badge_mint, minted[0].soulbound), (user, badge_mint, true));
+    let record: BadgeRecord = anchor_state(&mut ctx, pda(&[BADGE_RECORD_SEED, badge_mint.as_ref()])).await;
+    assert!(record.soulbound);
+    assert_eq!(badge_holder_page(&mut ctx, &badges.coin.meme_mint, 0).await.holders[0], user);
+
+    let mint = ctx.banks_client.get_account(badge_mint).await.unwrap().unwrap();
+    assert_eq!(mint.owner, spl_token_2022::ID);
+    let mint = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&mint.data).unwrap();
+    assert_eq!(mint.get_extension_types().unwrap(), vec![ExtensionType::NonTransferable]);
+    assert_eq!(mint.base.mint_authority, COption::Some(badges.contract()));
+    assert_eq!(mint.base.freeze_authority, COption::Some(badges.contract()));
+
+    // The badge cannot leave the earner's wallet
+    let user_ata = get_associated_token_address_with_program_id(&user, &badge_mint, &spl_token_2022::ID);
+    let recipient = Pubkey::new_unique();
+    let recipient_ata = get_associated_token_address_with_program_id(&recipient, &badge_mint, &spl_token_2022::ID);
+    let create_recipient_ata = Instruction {
+        program_id: associated_token::ID,
+        accounts: vec![
+            AccountMeta::new(user, true),
+            AccountMeta::new(recipient_ata, false),
+            AccountMeta::new_readonly(recipient, false),
+            AccountMeta::new_readonly(badge_mint, false),
+            AccountMeta::new_readonly(solana_sdk::system_program::ID, false),
+            AccountMeta::new_readonly(spl_token_2022::ID, false),
+        ],
+        data: vec![0], // Create
+    };
+    let transfer = spl_token_2022::instruction::transfer_checked(&spl_token_2022::ID, &user_ata, &badge_mint, &recipient_ata, &user, &[], 1, 0).unwrap();
+    let result = send(&mut ctx, &[create_recipient_ata, transfer], &[&trader.user]).await;
+    assert_custom_error(result, 1, TokenError::NonTransferable as u32);
+    let balance = StateWithExtensions::<spl_token_2022::state::Account>::unpack(&account_data(&mut ctx, user_ata).await).unwrap().base.amount;
+    assert_eq!(balance, 1);
+}
+
+#[tokio::test]
+async fn soulbound_badge_rejects_a_non_earner() {
+    let (mut ctx, badges) = start().await;
+    send(&mut ctx, &[initialize_badge_edition_ix(&badges, true)], &[&badges.deployer]).await.unwrap();
+    let earner = new_trader(&mut ctx, 10 * LAMPORTS_PER_SOL).await;
+    tax_swap(&mut ctx, &badges.coin, &earner, LAMPORTS_PER_SOL, true).await.unwrap();
+    let outsider = new_trader(&mut ctx, 10 * LAMPORTS_PER_SOL).await;
+    tax_swap(&mut ctx, &badges.coin, &outsider, LAMPORTS_PER_SOL / 2, true).await.unwrap();
+
+    // Someone else's buy counter doesn't derive from the claimant's key
+    let borrowed = mint_soulbound_badge_ix(&mut ctx, &badges, &outsider.user.pubkey(), &earner.user.pubkey()).await;
+    let result = send(&mut ctx, &[borrowed], &[&outsider.user]).await;
+    assert_custom_error(result, 0, anchor_lang::error::ErrorCode::ConstraintSeeds.into());
+
+    // Their own counter is below the lowest tier
+    let own = mint_soulbound_badge_ix(&mut ctx, &badges, &outsider.user.pubkey(), &outsider.user.pubkey()).await;
+    let result = send(&mut ctx, &[own], &[&outsider.user]).await;
+    assert_custom_error(result, 0, SafePumpError::InsufficientBuySwaps.into());
+
+    let holders = badge_holders(&mut ctx, &badges.coin.meme_mint).await;
+    assert_eq!(holders.holder_count, 0);
+}
+
+#[tokio::test]
+async fn each_badge_path_refuses_the_other_mode() {
+    for soulbound in [false, true] {
+        let (mut ctx, badges) = start().await;
+        send(&mut ctx, &[initialize_badge_edition_ix(&badges, soulbound)], &[&badges.deployer]).await.unwrap();
+        let trader = new_trader(&mut ctx, 10 * LAMPORTS_PER_SOL).await;
+        let user = trader.user.pubkey();
+        tax_swap(&mut ctx, &badges.coin, &trader, LAMPORTS_PER_SOL, true).await.unwrap();
+
+        let wrong_path = if soulbound {
+            mint_badge_ix(&mut ctx, &badges, &user).await
+        } else {
+            mint_soulbound_badge_ix(&mut ctx, &badges, &user, &user).await
+        };
+        let result = send(&mut ctx, &[wrong_path], &[&trader.user]).await;
+        assert_custom_error(result, 0, SafePumpError::BadgeModeMismatch.into());
+        assert_eq!(badge_holders(&mut ctx, &badges.coin.meme_mint).await.holder_count, 0);
+    }
+}
+
+/// A funded user whose buy counter reads `buy_swaps` buys worth `buy_volume`.
+async fn buyer_with_history(ctx: &mut ProgramTestContext, badges: &Badges, buy_swaps: u64, buy_volume: u64) -> Keypair {
+    let user = funded_keypair(ctx).await;
+    let meme_mint = badges.coin.meme_mint;
+    let (address, bump) = Pubkey::find_program_address(&[BUY_COUNTER_SEED, meme_mint.as_ref(), user.pubkey().as_ref()], &safe_pump::ID);
+    let counter = BuyCounter { meme_mint, user: user.pubkey(), buy_swaps, buy_volume, last_buy_at: 0, badges_revoked: 0, bump };
+    ctx.set_account(&address, &anchor_account(&counter, 8 + 32 + 32 + 8 + 8 + 8 + 8 + 1).into());
+    user
+}
+
+#[tokio::test]
+async fn mint_badge_picks_the_highest_tier_the_counter_meets() {
+    let (mut ctx, badges) = start().await;
+    send(&mut ctx, &[initialize_badge_edition_ix(&badges, false)], &[&badges.deployer]).await.unwrap();
+
+    // (buy swaps, buy volume) -> (tier, weight) against TEST_BADGE_TIERS
+    let cases = [
+        ((1, LAMPORTS_PER_SOL), (0, 1)),
+        ((5, 5 * LAMPORTS_PER_SOL), (1, 2)),
+        ((10, 10 * LAMPORTS_PER_SOL), (2, 4)),
+        // gold's swap count but only silver's volume
+        ((12, 7 * LAMPORTS_PER_SOL), (1, 2)),
+        // plenty of volume in too few swaps
+        ((4, 50 * LAMPORTS_PER_SOL), (0, 1)),
+    ];
+    let mut total_weight = 0;
+    for ((buy_swaps, buy_volume), (tier, weight)) in cases {
+        let user = buyer_with_history(&mut ctx, &badges, buy_swaps, buy_volume).await;
+        let mint_badge = mint_badge_ix(&mut ctx, &badges, &user.pubkey()).await;
+        let logs = send_with_logs(&mut ctx, &[mint_badge], &[&user]).await.unwrap();
+        assert_eq!(events::<BadgeMinted>(&logs)[0].tier, tier, "{buy_swaps} buys of {buy_volume}");
+
+        let badge_mint = badges.badge_mint(&user.pubkey(), 0);
+        let record: BadgeRecord = anchor_state(&mut ctx, pda(&[BADGE_RECORD_SEED, badge_mint.as_ref()])).await;
+        assert_eq!((record.tier, record.weight), (tier, weight), "{buy_swaps} buys of {buy_volume}");
+        total_weight += weight;
+    }
+    let holders = badge_holders(&mut ctx, &badges.coin.meme_mint).await;
+    assert_eq!((holders.holder_count, holders.total_badge_weight), (cases.len() as u64, total_weight));
+}
+
+fn claim_badge_rewards_ix(badges: &Badges, user: &Pubkey, user_wsol: Pubkey) -> Instruction {
+    let badge_mint = badges.badge_mint(user, 0);
+    ix(
+        safe_pump::accounts::ClaimBadgeRewards {
+            global_state: global_state_address(),
+            badge_holders: pda(&[BADGE_HOLDERS_SEED, badges.coin.meme_mint.as_ref()]),
+            badge_record: pda(&[BADGE_RECORD_SEED, badge_mint.as_ref()]),
+            badge_mint,
+            user_badge_ata: get_associated_token_address(user, &badge_mint),
+            rewards_vault: pda(&[REWARDS_VAULT_SEED]),
+            user_wsol,
+            user: *user,
+            token_program: spl_token::ID,
+        },
+        safe_pump::instruction::ClaimBadgeRewards {},
+    )
+}
+
+#[tokio::test]
+async fn badge_tax_splits_by_tier_weight() {
+    let (mut ctx, badges) = start().await;
+    send(&mut ctx, &[initialize_badge_edition_ix(&badges, false)], &[&badges.deployer]).await.unwrap();
+    let bronze = buyer_with_history(&mut ctx, &badges, 1, LAMPORTS_PER_SOL).await;
+    let gold = buyer_with_history(&mut ctx, &badges, 10, 10 * LAMPORTS_PER_SOL).await;
+    for user in [&bronze, &gold] {
+        let mint_badge = mint_badge_ix(&mut ctx, &badges, &user.pubkey()).await;
+        send(&mut ctx, &[mint_badge], &[user]).await.unwrap();
+    }
+    assert_eq!(badge_holders(&mut ctx, &badges.coin.meme_mint).await.total_badge_weight, 1 + 4);
+
+    let trader = new_trader(&mut ctx, 10 * LAMPORTS_PER_SOL).await;
+    tax_swap(&mut ctx, &badges.coin, &trader, LAMPORTS_PER_SOL, true).await.unwrap();
+    let badge_tax = LAMPORTS_PER_SOL * TAX_SPLIT.badge_reward_bps / 10_000;
+
+    let mut claimed = vec![];
+    for user in [&bronze, &gold] {
+        let user_wsol = Pubkey::new_unique();
+        ctx.set_account(&user_wsol, &wsol_account(&user.pubkey(), 0).into());
+        send(&mut ctx, &[claim_badge_rewards_ix(&badges, &user.pubkey(), user_wsol)], &[user]).await.unwrap();
+        claimed.push(token_balance(&mut ctx, user_wsol).await);
+    }
+    // Shares follow weight 1 : 4 and together use up the whole badge share
+    assert_eq!(claimed, vec![badge_tax / 5, badge_tax * 4 / 5]);
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// REVOCATION
+// ─────────────────────────────────────────────────────────────────────────────
+const BADGE_EXPIRY: i64 = 30 * 86_400;
+
+async fn set_badge_expiry(ctx: &mut ProgramTestContext, expiry_secs: i64) {
+    let mut config: BadgeConfig = anchor_state(ctx, pda(&[BADGE_CONFIG_SEED])).await;
+    config.expiry_secs = expiry_secs;
+    ctx.set_account(&pda(&[BADGE_CONFIG_SEED]), &anchor_account(&config, 256).into());
+}
+
+async fn advance_clock(ctx: &mut ProgramTestContext, secs: i64) {
+    ctx.get_new_latest_blockhash().await.unwrap();
+    let mut clock = ctx.banks_client.get_sysvar::<Clock>().await.unwrap();
+    clock.unix_timestamp += secs;
+    ctx.set_sysvar(&clock);
+}
+
+/// Revokes `owner`'s badge `badge_mint`, listed on holder page `holder_page`.
+async fn revoke_ix(ctx: &mut ProgramTestContext, badges: &Badges, owner: &Pubkey, badge_mint: Pubkey, holder_page: u64) -> Instruction {
+    let meme_mint = badges.coin.meme_mint;
+    let holders = badge_holders(ctx, &meme_mint).await;
+    ix(
+        safe_pump::accounts::RevokeInactiveBadge {
+            badge_config: pda(&[BADGE_CONFIG_SEED]),
+            badge_record: pda(&[BADGE_RECORD_SEED, badge_mint.as_ref()]),
+            buy_counter: pda(&[BUY_COUNTER_SEED, meme_mint.as_ref(), owner.as_ref()]),
+            badge_holders: pda(&[BADGE_HOLDERS_SEED, meme_mint.as_ref()]),
+            holder_page: badge_holder_page_address(&meme_mint, holder_page),
+            settled_page: badge_holder_page_address(&meme_mint, holders.settled_page()),
+            last_page: badge_holder_page_address(&meme_mint, holders.last_page()),
+            earner: *owner,
+            global_state: global_state_address(),
+            rewards_vault: pda(&[REWARDS_VAULT_SEED]),
+            treasury_vault: badges.coin.treasury_vault(),
+            badge_mint,
+            earner_badge_ata: get_associated_token_address_with_program_id(owner, &badge_mint, &spl_token_2022::ID),
+            contract: badges.contract(),
+            deployer: badges.deployer.pubkey(),
+            revoker: ctx.payer.pubkey(),
+            token_program: spl_token::ID,
+            token_2022_program: spl_token_2022::ID,
+        },
+        safe_pump::instruction::RevokeInactiveBadge {},
+    )
+}
+
+/// Mints `user` an edition badge and returns its mint.
+async fn mint_edition_badge(ctx: &mut ProgramTestContext, badges: &Badges, user: &Keypair) -> Pubkey {
+    let mint_badge = mint_badge_ix(ctx, badges, &user.pubkey()).await;
+    send(ctx, &[mint_badge], &[user]).await.unwrap();
+    badges.badge_mint(&user.pubkey(), 0)
+}
+
+#[tokio::test]
+async fn revoke_waits_out_the_badge_expiry() {
+    let (mut ctx, badges) = start().await;
+    send(&mut ctx, &[initialize_badge_edition_ix(&badges, false)], &[&badges.deployer]).await.unwrap();
+    let trader = new_trader(&mut ctx, 10 * LAMPORTS_PER_SOL).await;
+    let user = trader.user.pubkey();
+    tax_swap(&mut ctx, &badges.coin, &trader, LAMPORTS_PER_SOL, true).await.unwrap();
+    let badge_mint = mint_edition_badge(&mut ctx, &badges, &trader.user).await;
+
+    // expiry_secs 0 turns revocation off
+    let revoke = revoke_ix(&mut ctx, &badges, &user, badge_mint, 0).await;
+    assert_custom_error(send(&mut ctx, std::slice::from_ref(&revoke), &[]).await, 0, SafePumpError::BadgeStillActive.into());
+
+    set_badge_expiry(&mut ctx, BADGE_EXPIRY).await;
+    advance_clock(&mut ctx, BADGE_EXPIRY - 60).await;
+    assert_custom_error(send(&mut ctx, std::slice::from_ref(&revoke), &[]).await, 0, SafePumpError::BadgeStillActive.into());
+
+    advance_clock(&mut ctx, 60).await;
+    let logs = send_with_logs(&mut ctx, &[revoke], &[]).await.unwrap();
+    let revoked = events::<BadgeRevoked>(&logs);
+    assert_eq!((revoked[0].user, revoked[0].badge_mint, revoked[0].forfeited), (user, badge_mint, 0));
+
+    assert!(ctx.banks_client.get_account(pda(&[BADGE_RECORD_SEED, badge_mint.as_ref()])).await.unwrap().is_none());
+    let holders = badge_holders(&mut ctx, &badges.coin.meme_mint).await;
+    assert_eq!((holders.holder_count, holders.total_badge_weight), (0, 0));
+    assert_eq!(badge_holder_page(&mut ctx, &badges.coin.meme_mint, 0).await.holders[0], Pubkey::default());
+    let counter: BuyCounter = anchor_state(&mut ctx, pda(&[BUY_COUNTER_SEED, badges.coin.meme_mint.as_ref(), user.as_ref()])).await;
+    assert_eq!(counter.badges_revoked, 1);
+    // The print stays in the wallet; with its record gone it earns nothing
+    assert_eq!(token_balance(&mut ctx, get_associated_token_address(&user, &badge_mint)).await, 1);
+}
+
+/// A weight-1 badge record for `owner`, with a buy counter long past any expiry.
+fn add_badge_record(ctx: &mut ProgramTestContext, badges: &Badges, owner: &Pubkey) -> Pubkey {
+    let meme_mint = badges.coin.meme_mint;
+    let badge_mint = Pubkey::new_unique();
+    let (record, bump) = Pubkey::find_program_address(&[BADGE_RECORD_SEED, badge_mint.as_ref()], &safe_pump::ID);
+    let state = BadgeRecord { badge_mint, meme_mint, owner: *owner, reward_debt: 0, claimed: 0, soulbound: false, tier: 0, weight: 1, bump };
+    ctx.set_account(&record, &anchor_account(&state, 8 + 32 + 32 + 32 + 16 + 8 + 1 + 1 + 8 + 1).into());
+    let (counter, bump) = Pubkey::find_program_address(&[BUY_COUNTER_SEED, meme_mint.as_ref(), owner.as_ref()], &safe_pump::ID);
+    let state = BuyCounter { meme_mint, user: *owner, buy_swaps: 1, buy_volume: LAMPORTS_PER_SOL, last_buy_at: 0, badges_revoked: 0, bump };
+    ctx.set_account(&counter, &anchor_account(&state, 8 + 32 + 32 + 8 + 8 + 8 + 8 + 1).into());
+    badge_mint
+}
+
+#[tokio::test]
+async fn revoke_mid_crank_keeps_unpaid_holders_ahead_of_the_cursor() {
+    let (mut ctx, badges) = start().await;
+    set_badge_expiry(&mut ctx, BADGE_EXPIRY).await;
+    let meme_mint = badges.coin.meme_mint;
+
+    // 300 holders over two pages; the crank has paid the first 260
+    let list: Vec<Pubkey> = (0..300).map(|_| Pubkey::new_unique()).collect();
+    for (page, chunk) in list.chunks(256).enumerate() {
+        ctx.set_account(&badge_holder_page_address(&meme_mint, page as u64), &holder_page_account(&meme_mint, page as u64, chunk).into());
+    }
+    let mut holders = badge_holders(&mut ctx, &meme_mint).await;
+    (holders.holder_count, holders.total_badge_weight, holders.crank_cursor) = (300, 300, 260);
+    ctx.set_account(&pda(&[BADGE_HOLDERS_SEED, meme_mint.as_ref()]), &zero_copy_account(&holders).into());
+
+    // A paid holder on page 0 goes: the last paid one (page 1) fills the hole, the last
+    // holder overall takes the freed paid slot, and the cursor steps back onto it
+    let badge_mint = add_badge_record(&mut ctx, &badges, &list[5]);
+    let revoke = revoke_ix(&mut ctx, &badges, &list[5], badge_mint, 0).await;
+    send(&mut ctx, &[revoke], &[]).await.unwrap();
+    let holders = badge_holders(&mut ctx, &meme_mint).await;
+    assert_eq!((holders.holder_count, holders.crank_cursor), (299, 259));
+    let (first, second) = (badge_holder_page(&mut ctx, &meme_mint, 0).await, badge_holder_page(&mut ctx, &meme_mint, 1).await);
+    assert_eq!(first.holders[5], list[259]);
+    assert_eq!(second.holders[259 - 256], list[299]);
+    assert_eq!(second.holders[299 - 256], Pubkey::default());
+
+    // An unpaid holder is a plain swap-remove; the cursor stays
+    let badge_mint = add_badge_record(&mut ctx, &badges, &list[280]);
+    let revoke = revoke_ix(&mut ctx, &badges, &list[280], badge_mint, 1).await;
+    send(&mut ctx, &[revoke], &[]).await.unwrap();
+    let holders = badge_holders(&mut ctx, &meme_mint).await;
+    assert_eq!((holders.holder_count, holders.crank_cursor), (298, 259));
+    let second = badge_holder_page(&mut ctx, &meme_mint, 1).await;
+    assert_eq!(second.holders[280 - 256], list[298]);
+    assert_eq!(second.holders[298 - 256], Pubkey::default());
+    let mut listed: Vec<Pubkey> = [&first.holders[..], &second.holders[..298 - 256]].concat();
+    listed.sort();
+    let mut expected: Vec<Pubkey> = list.iter().filter(|h| **h != list[5] && **h != list[280]).copied().collect();
+    expected.sort();
+    assert_eq!(listed, expected);
+}
+
+#[tokio::test]
+async fn revoked_rewards_go_to_the_remaining_holders() {
+    let (mut ctx, badges) = start().await;
+    send(&mut ctx, &[initialize_badge_edition_ix(&badges, false)], &[&badges.deployer]).await.unwrap();
+    let bronze = buyer_with_history(&mut ctx, &badges, 1, LAMPORTS_PER_SOL).await;
+    let gold = buyer_with_history(&mut ctx, &badges, 10, 10 * LAMPORTS_PER_SOL).await;
+    let bronze_badge = mint_edition_badge(&mut ctx, &badges, &bronze).await;
+    mint_edition_badge(&mut ctx, &badges, &gold).await;
+    let trader = new_trader(&mut ctx, 10 * LAMPORTS_PER_SOL).await;
+    tax_swap(&mut ctx, &badges.coin, &trader, LAMPORTS_PER_SOL, true).await.unwrap();
+    let badge_tax = LAMPORTS_PER_SOL * TAX_SPLIT.badge_reward_bps / 10_000;
+
+    // Both counters date from time 0, so both badges have lapsed; only bronze is revoked
+    set_badge_expiry(&mut ctx, BADGE_EXPIRY).await;
+    let revoke = revoke_ix(&mut ctx, &badges, &bronze.pubkey(), bronze_badge, 0).await;
+    let logs = send_with_logs(&mut ctx, &[revoke], &[]).await.unwrap();
+    assert_eq!(events::<BadgeRevoked>(&logs)[0].forfeited, badge_tax / 5);
+
+    let gold_wsol = Pubkey::new_unique();
+    ctx.set_account(&gold_wsol, &wsol_account(&gold.pubkey(), 0).into());
+    send(&mut ctx, &[claim_badge_rewards_ix(&badges, &gold.pubkey(), gold_wsol)], &[&gold]).await.unwrap();
+    assert_eq!(token_balance(&mut ctx, gold_wsol).await, badge_tax);
+}
+
+#[tokio::test]
+async fn revoking_the_last_holder_sends_its_rewards_to_treasury() {
+    let (mut ctx, badges) = start().await;
+    send(&mut ctx, &[initialize_badge_edition_ix(&badges, false)], &[&badges.deployer]).await.unwrap();
+    let holder = buyer_with_history(&mut ctx, &badges, 1, LAMPORTS_PER_SOL).await;
+    let badge_mint = mint_edition_badge(&mut ctx, &badges, &holder).await;
+    let trader = new_trader(&mut ctx, 10 * LAMPORTS_PER_SOL).await;
+    tax_swap(&mut ctx, &badges.coin, &trader, LAMPORTS_PER_SOL, true).await.unwrap();
+    let badge_tax = LAMPORTS_PER_SOL * TAX_SPLIT.badge_reward_bps / 10_000;
+
+    set_badge_expiry(&mut ctx, BADGE_EXPIRY).await;
+    let treasury = token_balance(&mut ctx, badges.coin.treasury_vault()).await;
+    let rewards = token_balance(&mut ctx, pda(&[REWARDS_VAULT_SEED])).await;
+    let revoke = revoke_ix(&mut ctx, &badges, &holder.pubkey(), badge_mint, 0).await;
+    send(&mut ctx, &[revoke], &[]).await.unwrap();
+
+    assert_eq!(token_balance(&mut ctx, badges.coin.treasury_vault()).await, treasury + badge_tax);
+    assert_eq!(token_balance(&mut ctx, pda(&[REWARDS_VAULT_SEED])).await, rewards - badge_tax);
+    assert_eq!(badge_holders(&mut ctx, &badges.coin.meme_mint).await.total_badge_weight, 0);
+}
+
+#[tokio::test]
+async fn revoke_freezes_a_soulbound_badge() {
+    let (mut ctx, badges) = start().await;
+    send(&mut ctx, &[initialize_badge_edition_ix(&badges, true)], &[&badges.deployer]).await.unwrap();
+    let holder = buyer_with_history(&mut ctx, &badges, 1, LAMPORTS_PER_SOL).await;
+    let user = holder.pubkey();
+    let mint_badge = mint_soulbound_badge_ix(&mut ctx, &badges, &user, &user).await;
+    send(&mut ctx, &[mint_badge], &[&holder]).await.unwrap();
+    let badge_mint = badges.soulbound_badge_mint(&user, 0);
+
+    set_badge_expiry(&mut ctx, BADGE_EXPIRY).await;
+    let revoke = revoke_ix(&mut ctx, &badges, &user, badge_mint, 0).await;
+    send(&mut ctx, &[revoke], &[]).await.unwrap();
+
+    let ata = get_associated_token_address_with_program_id(&user, &badge_mint, &spl_token_2022::ID);
+    let account = StateWithExtensions::<spl_token_2022::state::Account>::unpack(&account_data(&mut ctx, ata).await).unwrap().base;
+    assert!(account.is_frozen());
+    assert_eq!(account.amount, 1);
+    assert!(ctx.banks_client.get_account(pda(&[BADGE_RECORD_SEED, badge_mint.as_ref()])).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn revoke_needs_the_soulbound_badge_account_of_the_earner() {
+    let (mut ctx, badges) = start().await;
+    send(&mut ctx, &[initialize_badge_edition_ix(&badges, true)], &[&badges.deployer]).await.unwrap();
+    let holder = buyer_with_history(&mut ctx, &badges, 1, LAMPORTS_PER_SOL).await;
+    let user = holder.pubkey();
+    let mint_badge = mint_soulbound_badge_ix(&mut ctx, &badges, &user, &user).await;
+    send(&mut ctx, &[mint_badge], &[&holder]).await.unwrap();
+    let badge_mint = badges.soulbound_badge_mint(&user, 0);
+
+    // Pointing the freeze elsewhere would leave the live badge untouched
+    set_badge_expiry(&mut ctx, BADGE_EXPIRY).await;
+    let mut revoke = revoke_ix(&mut ctx, &badges, &user, badge_mint, 0).await;
+    let ata = get_associated_token_address_with_program_id(&user, &badge_mint, &spl_token_2022::ID);
+    revoke.accounts.iter_mut().filter(|meta| meta.pubkey == ata).for_each(|meta| meta.pubkey = Pubkey::new_unique());
+    assert_custom_error(send(&mut ctx, &[revoke], &[]).await, 0, SafePumpError::InvalidBadgeAccount.into());
+}
@@ -0,0 +1,54 @@
+// The child-facing swap: the tax leg runs as a self-CPI into global_tax_swap before the
+// Raydium leg, so everything global_tax_swap writes must survive the outer instruction.
+mod common;
+
+use common::{raydium_program::add_raydium_program, *};
+use safe_pump::{BlockSwapState, BuyCounter, GlobalState, RewardDistribution, Vault, BUY_COUNTER_SEED, REWARDS_SEED, VAULT_SEED};
+use solana_program_test::ProgramTestContext;
+use solana_sdk::{clock::Clock, pubkey::Pubkey, signature::Signer};
+
+async fn start() -> (ProgramTestContext, Coin, Pubkey) {
+    let mut test = program_test();
+    add_raydium_program(&mut test);
+    let coin = add_coin(&mut test);
+    let deployer = add_badge_setup(&mut test, &coin);
+    let mut ctx = test.start_with_context().await;
+    init_vaults(&mut ctx, &coin).await;
+    (ctx, coin, contract_address(&deployer.pubkey()))
+}
+
+#[tokio::test]
+async fn swap_keeps_what_the_tax_leg_wrote() {
+    let (mut ctx, coin, contract) = start().await;
+    let trader = new_trader(&mut ctx, 10 * LAMPORTS_PER_SOL).await;
+    let slot = ctx.banks_client.get_sysvar::<Clock>().await.unwrap().slot;
+
+    child_swap(&mut ctx, &coin, &trader, &contract, LAMPORTS_PER_SOL, true).await.unwrap();
+
+    let user = trader.user.pubkey();
+    let global: GlobalState = anchor_state(&mut ctx, global_state_address()).await;
+    assert_eq!((global.swap_count, global.total_swapped), (1, LAMPORTS_PER_SOL));
+    let rewards: RewardDistribution = anchor_state(&mut ctx, pda(&[REWARDS_SEED])).await;
+    assert_eq!(rewards.swap_count, 1);
+    assert_eq!(rewards.participants, 1);
+    let velocity: BlockSwapState = anchor_state(&mut ctx, pda(&[b"velocity", &slot.to_le_bytes()])).await;
+    assert_eq!(velocity.total_bought_sol, LAMPORTS_PER_SOL);
+    let counter: BuyCounter = anchor_state(&mut ctx, pda(&[BUY_COUNTER_SEED, coin.meme_mint.as_ref(), user.as_ref()])).await;
+    assert_eq!(counter.buy_swaps, 1);
+    assert_eq!(anchor_state::<Vault>(&mut ctx, pda(&[VAULT_SEED, user.as_ref()])).await.nonce, 1);
+}
+
+#[tokio::test]
+async fn second_swap_in_a_slot_adds_to_the_velocity_total() {
+    let (mut ctx, coin, contract) = start().await;
+    let trader = new_trader(&mut ctx, 10 * LAMPORTS_PER_SOL).await;
+    let slot = ctx.banks_client.get_sysvar::<Clock>().await.unwrap().slot;
+
+    child_swap(&mut ctx, &coin, &trader, &contract, LAMPORTS_PER_SOL, true).await.unwrap();
+    child_swap(&mut ctx, &coin, &trader, &contract, 2 * LAMPORTS_PER_SOL, true).await.unwrap();
+
+    let velocity: BlockSwapState = anchor_state(&mut ctx, pda(&[b"velocity", &slot.to_le_bytes()])).await;
+    assert_eq!(velocity.total_bought_sol, 3 * LAMPORTS_PER_SOL);
+    let global: GlobalState = anchor_state(&mut ctx, global_state_address()).await;
+    assert_eq!(global.swap_count, 2);
+}
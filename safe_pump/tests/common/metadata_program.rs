@@ -0,0 +1,256 @@
+// Local stand-in for the Metaplex token metadata program. Handles the three
+// instructions the badge code calls, runs the checks safe_pump leans on the real
+// program for, and writes real Metadata / MasterEdition / Edition accounts.
+use anchor_spl::metadata::mpl_token_metadata::{
+    accounts::{Edition, EditionMarker, MasterEdition, Metadata},
+    instructions::{
+        CreateMasterEditionV3InstructionArgs, CreateMetadataAccountV3InstructionArgs,
+        MintNewEditionFromMasterEditionViaTokenInstructionArgs,
+    },
+    types::Key,
+    ID,
+};
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program_test::{processor, ProgramTest};
+use solana_sdk::{
+    account_info::AccountInfo,
+    entrypoint::ProgramResult,
+    program::invoke_signed,
+    program_error::ProgramError,
+    program_option::COption,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    rent::Rent,
+    sysvar::Sysvar,
+};
+use solana_system_interface::instruction as system_instruction;
+use spl_token::instruction::{set_authority, AuthorityType};
+
+const CREATE_METADATA_ACCOUNT_V3: u8 = 33;
+const CREATE_MASTER_EDITION_V3: u8 = 17;
+const MINT_NEW_EDITION_FROM_MASTER_EDITION_VIA_TOKEN: u8 = 11;
+
+// Metaplex pads these fields with NULs up to their maximum length
+const MAX_NAME_LENGTH: usize = 32;
+const MAX_SYMBOL_LENGTH: usize = 10;
+const MAX_URI_LENGTH: usize = 200;
+const EDITION_MARKER_BIT_SIZE: u64 = 248;
+
+pub fn add_metadata_program(test: &mut ProgramTest) {
+    test.add_program("mpl_token_metadata", ID, processor!(process_instruction));
+}
+
+pub fn metadata_address(mint: &Pubkey) -> Pubkey {
+    Metadata::find_pda(mint).0
+}
+
+pub fn edition_address(mint: &Pubkey) -> Pubkey {
+    MasterEdition::find_pda(mint).0
+}
+
+pub fn edition_marker_address(master_mint: &Pubkey, edition: u64) -> Pubkey {
+    EditionMarker::find_pda(master_mint, &(edition / EDITION_MARKER_BIT_SIZE).to_string()).0
+}
+
+/// Metadata as the real program would have written it for a meme coin.
+pub fn metadata_account(mint: &Pubkey, name: &str, symbol: &str, uri: &str) -> solana_sdk::account::Account {
+    let metadata = Metadata {
+        key: Key::MetadataV1,
+        update_authority: Pubkey::new_unique(),
+        mint: *mint,
+        name: puff(name, MAX_NAME_LENGTH),
+        symbol: puff(symbol, MAX_SYMBOL_LENGTH),
+        uri: puff(uri, MAX_URI_LENGTH),
+        seller_fee_basis_points: 0,
+        creators: None,
+        primary_sale_happened: false,
+        is_mutable: true,
+        edition_nonce: None,
+        token_standard: None,
+        collection: None,
+        uses: None,
+        collection_details: None,
+        programmable_config: None,
+    };
+    solana_sdk::account::Account {
+        lamports: 1_000_000_000,
+        data: metadata.try_to_vec().unwrap(),
+        owner: ID,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+fn process_instruction(_program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let (tag, args) = data.split_first().ok_or(ProgramError::InvalidInstructionData)?;
+    match *tag {
+        CREATE_METADATA_ACCOUNT_V3 => create_metadata_account_v3(accounts, args),
+        CREATE_MASTER_EDITION_V3 => create_master_edition_v3(accounts, args),
+        MINT_NEW_EDITION_FROM_MASTER_EDITION_VIA_TOKEN => mint_new_edition_from_master_edition_via_token(accounts, args),
+        _ => Err(ProgramError::InvalidInstructionData),
+    }
+}
+
+fn create_metadata_account_v3(accounts: &[AccountInfo], args: &[u8]) -> ProgramResult {
+    let [metadata, mint, mint_authority, payer, update_authority, system_program, ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    let args = CreateMetadataAccountV3InstructionArgs::try_from_slice(args).map_err(|_| ProgramError::InvalidInstructionData)?;
+    require_signers(&[mint_authority, payer])?;
+    let mint_state = unpack_mint(mint)?;
+    if mint_state.mint_authority != COption::Some(*mint_authority.key) {
+        return Err(ProgramError::IllegalOwner);
+    }
+
+    let state = Metadata {
+        key: Key::MetadataV1,
+        update_authority: *update_authority.key,
+        mint: *mint.key,
+        name: puff(&args.data.name, MAX_NAME_LENGTH),
+        symbol: puff(&args.data.symbol, MAX_SYMBOL_LENGTH),
+        uri: puff(&args.data.uri, MAX_URI_LENGTH),
+        seller_fee_basis_points: args.data.seller_fee_basis_points,
+        creators: args.data.creators,
+        primary_sale_happened: false,
+        is_mutable: args.is_mutable,
+        edition_nonce: Some(MasterEdition::find_pda(mint.key).1),
+        token_standard: None,
+        collection: args.data.collection,
+        uses: args.data.uses,
+        collection_details: args.collection_details,
+        programmable_config: None,
+    };
+    create_pda(payer, metadata, system_program, &[b"metadata", ID.as_ref(), mint.key.as_ref()], &state)
+}
+
+fn create_master_edition_v3(accounts: &[AccountInfo], args: &[u8]) -> ProgramResult {
+    let [edition, mint, update_authority, mint_authority, payer, metadata, token_program, system_program, ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    let args = CreateMasterEditionV3InstructionArgs::try_from_slice(args).map_err(|_| ProgramError::InvalidInstructionData)?;
+    require_signers(&[update_authority, mint_authority, payer])?;
+    let metadata_state = read_metadata(metadata)?;
+    if metadata_state.mint != *mint.key || metadata_state.update_authority != *update_authority.key {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    let mint_state = unpack_mint(mint)?;
+    if mint_state.supply != 1 || mint_state.decimals != 0 || mint_state.mint_authority != COption::Some(*mint_authority.key) {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let state = MasterEdition { key: Key::MasterEditionV2, supply: 0, max_supply: args.max_supply };
+    create_pda(payer, edition, system_program, &[b"metadata", ID.as_ref(), mint.key.as_ref(), b"edition"], &state)?;
+    hand_mint_to_edition(token_program, mint, mint_authority, edition)
+}
+
+fn mint_new_edition_from_master_edition_via_token(accounts: &[AccountInfo], args: &[u8]) -> ProgramResult {
+    let [new_metadata, new_edition, master_edition, new_mint, edition_mark, new_mint_authority, payer, token_account_owner, token_account, new_metadata_update_authority, metadata, token_program, system_program, ..] =
+        accounts
+    else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    let edition = MintNewEditionFromMasterEditionViaTokenInstructionArgs::try_from_slice(args)
+        .map_err(|_| ProgramError::InvalidInstructionData)?
+        .mint_new_edition_from_master_edition_via_token_args
+        .edition;
+    require_signers(&[new_mint_authority, payer, token_account_owner])?;
+
+    // The caller must hold the master token
+    let master = read_metadata(metadata)?;
+    let token = spl_token::state::Account::unpack(&token_account.try_borrow_data()?)?;
+    if token.mint != master.mint || token.owner != *token_account_owner.key || token.amount != 1 {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    if *master_edition.key != edition_address(&master.mint) || master_edition.owner != &ID {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    let mut master_state = MasterEdition::safe_deserialize(&master_edition.try_borrow_data()?).map_err(|_| ProgramError::InvalidAccountData)?;
+    if edition == 0 || master_state.max_supply.is_some_and(|max| master_state.supply >= max || edition > max) {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    // A print is a decimals-0 mint with exactly one token, minted before the call
+    let mint_state = unpack_mint(new_mint)?;
+    if mint_state.supply != 1 || mint_state.decimals != 0 || mint_state.mint_authority != COption::Some(*new_mint_authority.key) {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    // Each edition number can be printed once
+    let marker_seed = (edition / EDITION_MARKER_BIT_SIZE).to_string();
+    let marker_seeds: &[&[u8]] = &[b"metadata", ID.as_ref(), master.mint.as_ref(), b"edition", marker_seed.as_bytes()];
+    if edition_mark.data_is_empty() {
+        create_pda(payer, edition_mark, system_program, marker_seeds, &EditionMarker { key: Key::EditionMarker, ledger: [0; 31] })?;
+    } else if *edition_mark.key != edition_marker_address(&master.mint, edition) {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    let mut marker = EditionMarker::from_bytes(&edition_mark.try_borrow_data()?).map_err(|_| ProgramError::InvalidAccountData)?;
+    let bit = (edition % EDITION_MARKER_BIT_SIZE) as usize;
+    let mask = 1u8 << (7 - bit % 8);
+    if marker.ledger[bit / 8] & mask != 0 {
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+    marker.ledger[bit / 8] |= mask;
+    write(edition_mark, &marker)?;
+
+    let print = Metadata { mint: *new_mint.key, update_authority: *new_metadata_update_authority.key, ..master };
+    create_pda(payer, new_metadata, system_program, &[b"metadata", ID.as_ref(), new_mint.key.as_ref()], &print)?;
+    let print_edition = Edition { key: Key::EditionV1, parent: *master_edition.key, edition };
+    create_pda(payer, new_edition, system_program, &[b"metadata", ID.as_ref(), new_mint.key.as_ref(), b"edition"], &print_edition)?;
+
+    master_state.supply += 1;
+    write(master_edition, &master_state)?;
+    hand_mint_to_edition(token_program, new_mint, new_mint_authority, new_edition)
+}
+
+fn require_signers(accounts: &[&AccountInfo]) -> ProgramResult {
+    match accounts.iter().all(|a| a.is_signer) {
+        true => Ok(()),
+        false => Err(ProgramError::MissingRequiredSignature),
+    }
+}
+
+fn unpack_mint(mint: &AccountInfo) -> Result<spl_token::state::Mint, ProgramError> {
+    spl_token::state::Mint::unpack(&mint.try_borrow_data()?)
+}
+
+fn read_metadata(metadata: &AccountInfo) -> Result<Metadata, ProgramError> {
+    if metadata.owner != &ID {
+        return Err(ProgramError::IllegalOwner);
+    }
+    Metadata::safe_deserialize(&metadata.try_borrow_data()?).map_err(|_| ProgramError::InvalidAccountData)
+}
+
+fn puff(s: &str, len: usize) -> String {
+    format!("{s:\0<len$}")
+}
+
+/// Creates the PDA at `seeds` under the metadata program and writes `state` into it.
+fn create_pda<'a, T: BorshSerialize>(payer: &AccountInfo<'a>, account: &AccountInfo<'a>, system_program: &AccountInfo<'a>, seeds: &[&[u8]], state: &T) -> ProgramResult {
+    let (address, bump) = Pubkey::find_program_address(seeds, &ID);
+    if *account.key != address {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    let data = state.try_to_vec().map_err(|_| ProgramError::InvalidAccountData)?;
+    let create = system_instruction::create_account(payer.key, account.key, Rent::get()?.minimum_balance(data.len()), data.len() as u64, &ID);
+    let bump = [bump];
+    let signer_seeds: Vec<&[u8]> = seeds.iter().copied().chain([&bump[..]]).collect();
+    invoke_signed(&create, &[payer.clone(), account.clone(), system_program.clone()], &[&signer_seeds])?;
+    account.try_borrow_mut_data()?.copy_from_slice(&data);
+    Ok(())
+}
+
+fn write<T: BorshSerialize>(account: &AccountInfo, state: &T) -> ProgramResult {
+    let data = state.try_to_vec().map_err(|_| ProgramError::InvalidAccountData)?;
+    account.try_borrow_mut_data()?[..data.len()].copy_from_slice(&data);
+    Ok(())
+}
+
+/// Like the real program, takes mint and freeze authority away from the caller: once
+/// an edition exists, nobody can mint a second token of it.
+fn hand_mint_to_edition<'a>(token_program: &AccountInfo<'a>, mint: &AccountInfo<'a>, authority: &AccountInfo<'a>, edition: &AccountInfo<'a>) -> ProgramResult {
+    for kind in [AuthorityType::MintTokens, AuthorityType::FreezeAccount] {
+        let ix = set_authority(token_program.key, mint.key, Some(edition.key), kind, authority.key, &[])?;
+        invoke_signed(&ix, &[mint.clone(), authority.clone(), token_program.clone()], &[])?;
+    }
+    Ok(())
+}
@@ -0,0 +1,742 @@
+// Shared harness: runs safe_pump natively inside solana-program-test.
+#![allow(dead_code)]
+
+pub mod metadata_program;
+pub mod raydium_program;
+
+use anchor_lang::{solana_program::{program_stubs::{self, SyscallStubs}, sysvar}, AccountDeserialize, AccountSerialize, AnchorDeserialize, Discriminator, InstructionData, Space, ToAccountMetas};
+use ark_bn254::{Fr, G2Affine};
+use ark_ec::{AffineRepr, CurveGroup};
+use ark_ff::{BigInteger, PrimeField};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use bytemuck::Zeroable;
+use safe_pump::{
+    BadgeConfig, BadgeHolderPage, BadgeHolders, BadgeTier, CoinStatus, GlobalState, RegistryEntry, RewardDistribution, TaxSchedule, TaxSplit, TokenContract, Vault,
+    BADGE_CONFIG_SEED, BADGE_HOLDERS_SEED, BADGE_HOLDER_PAGE_SEED, BUY_COUNTER_SEED, LP_TAX_VAULT_SEED, REGISTRY_ENTRY_SEED, REWARDS_SEED, REWARDS_VAULT_SEED,
+    REWARD_ACCRUAL_SEED, TAX_SCHEDULE_SEED, TREASURY_VAULT_SEED, VAULT_SEED,
+};
+use safe_pump_interface::{
+    swap_authorization::{SwapAuthorization, SWAP_AUTHORIZATION_DST},
+    swap_authorizer::{hash_to_g1, VaultAuthKey},
+    vault_key::{vault_key_pop_message, VAULT_KEY_POP_DST},
+};
+use solana_bn254::prelude::alt_bn128_multiplication;
+use solana_ed25519_program::new_ed25519_instruction_with_signature;
+use solana_program_test::{processor, BanksClientError, ProgramTest, ProgramTestContext};
+use solana_sdk::{
+    account::Account,
+    account_info::AccountInfo,
+    clock::Clock,
+    entrypoint::ProgramResult,
+    instruction::{Instruction, InstructionError},
+    program_option::COption,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    rent::Rent,
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+use solana_system_interface::instruction as system_instruction;
+use spl_token::native_mint;
+use std::sync::{Once, OnceLock};
+
+pub const LAMPORTS_PER_SOL: u64 = 1_000_000_000;
+pub const WITHDRAWAL_DELAY: i64 = 172_800;
+// The launch split: 2.5% total, of which 1% LP, 0.8% swapper, 0.2% badge, 0.5% treasury
+pub const TAX_SPLIT: TaxSplit = TaxSplit { total_bps: 250, lp_bps: 100, swapper_reward_bps: 80, badge_reward_bps: 20, treasury_bps: 50 };
+
+fn process_instruction(program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    // Anchor's entry wants `&'info [AccountInfo<'info>]`; the harness hands out shorter
+    // borrows, so give it an owned copy that lives for the rest of the test
+    let accounts = Box::leak(Box::new(accounts.to_vec()));
+    safe_pump::entry(program_id, accounts, data)
+}
+
+pub fn program_test() -> ProgramTest {
+    let mut test = ProgramTest::new("safe_pump", safe_pump::ID, processor!(process_instruction));
+    test.prefer_bpf(false);
+    test
+}
+
+pub fn ix(accounts: impl ToAccountMetas, data: impl InstructionData) -> Instruction {
+    Instruction { program_id: safe_pump::ID, accounts: accounts.to_account_metas(None), data: data.data() }
+}
+
+/// Marks `signer` as signing `ix`. Admin-gated instructions take the authority as an
+/// unchecked account (it may be an AdminCouncil PDA), so Anchor doesn't flag it.
+pub fn signed_by(mut ix: Instruction, signer: &Pubkey) -> Instruction {
+    ix.accounts.iter_mut().filter(|meta| meta.pubkey == *signer).for_each(|meta| meta.is_signer = true);
+    ix
+}
+
+/// An Anchor account owned by safe_pump, already serialized with its discriminator.
+pub fn anchor_account<T: AccountSerialize>(state: &T, space: usize) -> Account {
+    let mut data = Vec::with_capacity(space);
+    state.try_serialize(&mut data).unwrap();
+    data.resize(space, 0);
+    Account { lamports: 1_000_000_000, data, owner: safe_pump::ID, executable: false, rent_epoch: 0 }
+}
+
+/// Signs with `ctx.last_blockhash`; call `get_new_latest_blockhash` before resending an
+/// identical transaction, or the bank hands back the earlier result.
+pub async fn send(ctx: &mut ProgramTestContext, ixs: &[Instruction], signers: &[&Keypair]) -> Result<(), BanksClientError> {
+    let blockhash = ctx.last_blockhash;
+    let mut all: Vec<&Keypair> = vec![&ctx.payer];
+    all.extend_from_slice(signers);
+    let tx = Transaction::new_signed_with_payer(ixs, Some(&ctx.payer.pubkey()), &all, blockhash);
+    ctx.banks_client.process_transaction(tx).await
+}
+
+/// solana-program-test forwards `msg!` to the transaction log but prints `sol_log_data`
+/// (what `emit!` uses) to stdout. This routes it through `sol_log` instead, so events
+/// show up as `Program log: Program data: ...`.
+struct EventLogStubs;
+
+// The stubs program-test installed, which EventLogStubs forwards everything else to
+static PROGRAM_TEST_STUBS: OnceLock<Box<dyn SyscallStubs>> = OnceLock::new();
+
+fn inner() -> &'static dyn SyscallStubs {
+    PROGRAM_TEST_STUBS.wait().as_ref()
+}
+
+impl SyscallStubs for EventLogStubs {
+    fn sol_log(&self, message: &str) {
+        inner().sol_log(message)
+    }
+    fn sol_log_compute_units(&self) {
+        inner().sol_log_compute_units()
+    }
+    fn sol_remaining_compute_units(&self) -> u64 {
+        inner().sol_remaining_compute_units()
+    }
+    fn sol_invoke_signed(&self, instruction: &Instruction, account_infos: &[AccountInfo], signers_seeds: &[&[&[u8]]]) -> ProgramResult {
+        inner().sol_invoke_signed(instruction, account_infos, signers_seeds)
+    }
+    fn sol_get_sysvar(&self, sysvar_id_addr: *const u8, var_addr: *mut u8, offset: u64, length: u64) -> u64 {
+        inner().sol_get_sysvar(sysvar_id_addr, var_addr, offset, length)
+    }
+    fn sol_get_clock_sysvar(&self, var_addr: *mut u8) -> u64 {
+        inner().sol_get_clock_sysvar(var_addr)
+    }
+    fn sol_get_epoch_schedule_sysvar(&self, var_addr: *mut u8) -> u64 {
+        inner().sol_get_epoch_schedule_sysvar(var_addr)
+    }
+    fn sol_get_fees_sysvar(&self, var_addr: *mut u8) -> u64 {
+        inner().sol_get_fees_sysvar(var_addr)
+    }
+    fn sol_get_rent_sysvar(&self, var_addr: *mut u8) -> u64 {
+        inner().sol_get_rent_sysvar(var_addr)
+    }
+    fn sol_get_epoch_rewards_sysvar(&self, var_addr: *mut u8) -> u64 {
+        inner().sol_get_epoch_rewards_sysvar(var_addr)
+    }
+    fn sol_get_last_restart_slot(&self, var_addr: *mut u8) -> u64 {
+        inner().sol_get_last_restart_slot(var_addr)
+    }
+    fn sol_get_epoch_stake(&self, vote_address: *const u8) -> u64 {
+        inner().sol_get_epoch_stake(vote_address)
+    }
+    unsafe fn sol_memcpy(&self, dst: *mut u8, src: *const u8, n: usize) {
+        inner().sol_memcpy(dst, src, n)
+    }
+    unsafe fn sol_memmove(&self, dst: *mut u8, src: *const u8, n: usize) {
+        inner().sol_memmove(dst, src, n)
+    }
+    unsafe fn sol_memcmp(&self, s1: *const u8, s2: *const u8, n: usize, result: *mut i32) {
+        inner().sol_memcmp(s1, s2, n, result)
+    }
+    unsafe fn sol_memset(&self, s: *mut u8, c: u8, n: usize) {
+        inner().sol_memset(s, c, n)
+    }
+    fn sol_get_return_data(&self) -> Option<(Pubkey, Vec<u8>)> {
+        inner().sol_get_return_data()
+    }
+    fn sol_set_return_data(&self, data: &[u8]) {
+        inner().sol_set_return_data(data)
+    }
+    fn sol_log_data(&self, fields: &[&[u8]]) {
+        let fields: Vec<String> = fields.iter().map(|field| STANDARD.encode(field)).collect();
+        inner().sol_log(&format!("Program data: {}", fields.join(" ")))
+    }
+    fn sol_get_processed_sibling_instruction(&self, index: usize) -> Option<Instruction> {
+        inner().sol_get_processed_sibling_instruction(index)
+    }
+    fn sol_get_stack_height(&self) -> u64 {
+        inner().sol_get_stack_height()
+    }
+}
+
+/// Like `send`, returning the transaction's log on success.
+pub async fn send_with_logs(ctx: &mut ProgramTestContext, ixs: &[Instruction], signers: &[&Keypair]) -> Result<Vec<String>, TransactionError> {
+    // program-test installs its own stubs once per process when the first bank starts,
+    // which has happened by the time a context exists
+    static EVENT_LOGS: Once = Once::new();
+    EVENT_LOGS.call_once(|| {
+        let stubs = program_stubs::set_syscall_stubs(Box::new(EventLogStubs));
+        let _ = PROGRAM_TEST_STUBS.set(stubs);
+    });
+    let mut all: Vec<&Keypair> = vec![&ctx.payer];
+    all.extend_from_slice(signers);
+    let tx = Transaction::new_signed_with_payer(ixs, Some(&ctx.payer.pubkey()), &all, ctx.last_blockhash);
+    // This runs straight on the bank, so it can land while a transaction sent through
+    // `send` is still committing and holds its account locks
+    loop {
+        let outcome = ctx.banks_client.process_transaction_with_metadata(tx.clone()).await.unwrap();
+        match outcome.result {
+            Err(TransactionError::AccountInUse) => tokio::task::yield_now().await,
+            result => return result.map(|()| outcome.metadata.unwrap().log_messages),
+        }
+    }
+}
+
+/// Every `T` the transaction emitted, decoded from its `Program data:` log lines.
+pub fn events<T: AnchorDeserialize + Discriminator>(logs: &[String]) -> Vec<T> {
+    logs.iter()
+        .filter_map(|line| line.strip_prefix("Program log: Program data: "))
+        .filter_map(|data| STANDARD.decode(data).ok())
+        .filter(|bytes| bytes.starts_with(T::DISCRIMINATOR))
+        .map(|bytes| T::deserialize(&mut &bytes[T::DISCRIMINATOR.len()..]).unwrap())
+        .collect()
+}
+
+/// Gives `to` enough SOL to pay for the accounts it creates.
+pub async fn fund(ctx: &mut ProgramTestContext, to: &Pubkey) {
+    let transfer = system_instruction::transfer(&ctx.payer.pubkey(), to, 10_000_000_000);
+    send(ctx, &[transfer], &[]).await.unwrap();
+}
+
+pub async fn funded_keypair(ctx: &mut ProgramTestContext) -> Keypair {
+    let keypair = Keypair::new();
+    fund(ctx, &keypair.pubkey()).await;
+    keypair
+}
+
+/// Asserts `result` failed in instruction `index` with the given custom error code.
+pub fn assert_custom_error(result: Result<(), BanksClientError>, index: u8, code: u32) {
+    match result.expect_err("transaction should fail").unwrap() {
+        TransactionError::InstructionError(i, InstructionError::Custom(c)) => {
+            assert_eq!((i, c), (index, code), "unexpected failing instruction or error code");
+        }
+        other => panic!("unexpected error: {other:?}"),
+    }
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// COIN FIXTURE: an active, registered meme coin with its tax vaults, ready to trade
+// ─────────────────────────────────────────────────────────────────────────────
+pub fn pda(seeds: &[&[u8]]) -> Pubkey {
+    Pubkey::find_program_address(seeds, &safe_pump::ID).0
+}
+
+pub fn global_state_address() -> Pubkey {
+    pda(&[b"global"])
+}
+
+/// A WSOL token account holding `amount` wrapped lamports.
+pub fn wsol_account(owner: &Pubkey, amount: u64) -> Account {
+    let rent = Rent::default().minimum_balance(spl_token::state::Account::LEN);
+    let state = spl_token::state::Account {
+        mint: native_mint::ID,
+        owner: *owner,
+        amount,
+        state: spl_token::state::AccountState::Initialized,
+        is_native: COption::Some(rent),
+        ..Default::default()
+    };
+    let mut data = vec![0; spl_token::state::Account::LEN];
+    state.pack_into_slice(&mut data);
+    Account { lamports: rent + amount, data, owner: spl_token::ID, executable: false, rent_epoch: 0 }
+}
+
+pub fn token_account(mint: &Pubkey, owner: &Pubkey, amount: u64) -> Account {
+    let state = spl_token::state::Account {
+        mint: *mint,
+        owner: *owner,
+        amount,
+        state: spl_token::state::AccountState::Initialized,
+        ..Default::default()
+    };
+    let mut data = vec![0; spl_token::state::Account::LEN];
+    state.pack_into_slice(&mut data);
+    Account { lamports: 1_000_000_000, data, owner: spl_token::ID, executable: false, rent_epoch: 0 }
+}
+
+pub fn mint_account(authority: &Pubkey, decimals: u8) -> Account {
+    let state = spl_token::state::Mint { mint_authority: COption::Some(*authority), decimals, is_initialized: true, ..Default::default() };
+    let mut data = vec![0; spl_token::state::Mint::LEN];
+    state.pack_into_slice(&mut data);
+    Account { lamports: 1_000_000_000, data, owner: spl_token::ID, executable: false, rent_epoch: 0 }
+}
+
+pub async fn token_balance(ctx: &mut ProgramTestContext, address: Pubkey) -> u64 {
+    let account = ctx.banks_client.get_account(address).await.unwrap().expect("token account");
+    spl_token::state::Account::unpack(&account.data).unwrap().amount
+}
+
+pub async fn anchor_state<T: AccountDeserialize>(ctx: &mut ProgramTestContext, address: Pubkey) -> T {
+    let account = ctx.banks_client.get_account(address).await.unwrap().expect("account");
+    T::try_deserialize(&mut account.data.as_slice()).unwrap()
+}
+
+pub async fn badge_holders(ctx: &mut ProgramTestContext, meme_mint: &Pubkey) -> BadgeHolders {
+    let account = ctx.banks_client.get_account(pda(&[BADGE_HOLDERS_SEED, meme_mint.as_ref()])).await.unwrap().expect("badge holders");
+    bytemuck::pod_read_unaligned(&account.data[8..])
+}
+
+pub fn badge_holder_page_address(meme_mint: &Pubkey, page: u64) -> Pubkey {
+    pda(&[BADGE_HOLDER_PAGE_SEED, meme_mint.as_ref(), &page.to_le_bytes()])
+}
+
+pub async fn badge_holder_page(ctx: &mut ProgramTestContext, meme_mint: &Pubkey, page: u64) -> BadgeHolderPage {
+    let account = ctx.banks_client.get_account(badge_holder_page_address(meme_mint, page)).await.unwrap().expect("badge holder page");
+    bytemuck::pod_read_unaligned(&account.data[8..])
+}
+
+/// A zero-copy account owned by safe_pump, discriminator first.
+pub fn zero_copy_account<T: bytemuck::Pod + Discriminator>(state: &T) -> Account {
+    let data = [T::DISCRIMINATOR, bytemuck::bytes_of(state)].concat();
+    Account { lamports: 1_000_000_000, data, owner: safe_pump::ID, executable: false, rent_epoch: 0 }
+}
+
+/// Holder page `page` of `meme_mint` listing `holders` from its first slot.
+pub fn holder_page_account(meme_mint: &Pubkey, page: u64, holders: &[Pubkey]) -> Account {
+    let mut state = BadgeHolderPage::zeroed();
+    state.meme_mint = *meme_mint;
+    state.page = page;
+    state.bump = Pubkey::find_program_address(&[BADGE_HOLDER_PAGE_SEED, meme_mint.as_ref(), &page.to_le_bytes()], &safe_pump::ID).1;
+    state.holders[..holders.len()].copy_from_slice(holders);
+    zero_copy_account(&state)
+}
+
+pub struct Coin {
+    pub admin: Keypair,
+    pub meme_mint: Pubkey,
+    pub child_program_id: Pubkey,
+}
+
+impl Coin {
+    pub fn treasury_vault(&self) -> Pubkey {
+        pda(&[TREASURY_VAULT_SEED, self.meme_mint.as_ref()])
+    }
+
+    pub fn lp_vault(&self) -> Pubkey {
+        pda(&[LP_TAX_VAULT_SEED, self.meme_mint.as_ref()])
+    }
+}
+
+/// A fresh address with the vanity suffix `swap` requires of meme mints.
+pub fn meme_mint_address() -> Pubkey {
+    loop {
+        let unique = Pubkey::new_unique().to_string();
+        if let Ok(mint) = format!("{}SPMP", &unique[..unique.len() - 4]).parse() {
+            return mint;
+        }
+    }
+}
+
+/// Writes the state a handshake and the admin setup leave behind: GlobalState with the
+/// child approved, the global tax schedule, the rewards ledger, and the coin's registry
+/// entry and badge holder list with its first page.
+pub fn add_coin(test: &mut ProgramTest) -> Coin {
+    let coin = Coin { admin: Keypair::new(), meme_mint: meme_mint_address(), child_program_id: Pubkey::new_unique() };
+    let global = GlobalState {
+        is_initialized: true,
+        treasury_wallet: Pubkey::new_unique(),
+        total_swapped: 0,
+        swap_count: 0,
+        bond_timestamp: 0,
+        bump: Pubkey::find_program_address(&[b"global"], &safe_pump::ID).1,
+        admin: coin.admin.pubkey(),
+        pending_admin: None,
+        withdrawal_delay: WITHDRAWAL_DELAY,
+        epoch_withdrawal_cap: 100 * LAMPORTS_PER_SOL,
+        withdrawal_epoch: 0,
+        withdrawn_this_epoch: 0,
+        next_withdrawal_id: 0,
+        keeper_fee_bps: 10,
+        pending_withdrawal_limits: None,
+        withdrawal_limits_activate_at: 0,
+        guardian: Pubkey::default(),
+        pending_guardian: None,
+        guardian_activates_at: 0,
+        approved_child_programs: vec![coin.child_program_id],
+    };
+    test.add_account(global_state_address(), anchor_account(&global, 2048));
+
+    let (schedule, bump) = Pubkey::find_program_address(&[TAX_SCHEDULE_SEED], &safe_pump::ID);
+    test.add_account(schedule, anchor_account(&TaxSchedule { active: TAX_SPLIT, pending: None, activates_at: 0, bump }, 256));
+
+    let (rewards, bump) = Pubkey::find_program_address(&[REWARDS_SEED], &safe_pump::ID);
+    let ledger = RewardDistribution {
+        swapper_rewards_accrued: 0,
+        badge_rewards: 0,
+        swap_count: 0,
+        last_distribution_timestamp: 0,
+        current_epoch: 0,
+        participants: 0,
+        bump,
+    };
+    test.add_account(rewards, anchor_account(&ledger, 128));
+
+    let (entry, bump) = Pubkey::find_program_address(&[REGISTRY_ENTRY_SEED, coin.meme_mint.as_ref()], &safe_pump::ID);
+    let registry_entry = RegistryEntry {
+        meme_mint: coin.meme_mint,
+        child_program_id: coin.child_program_id,
+        tax_override: None,
+        index: 0,
+        deployer: Pubkey::new_unique(),
+        registered_slot: 0,
+        registered_at: 0,
+        bonded: false,
+        raydium_pool: Pubkey::default(),
+        status: CoinStatus::Active,
+        pending_tax_override: None,
+        tax_override_activates_at: 0,
+        bump,
+    };
+    test.add_account(entry, anchor_account(&registry_entry, 256));
+
+    let (holders_address, bump) = Pubkey::find_program_address(&[BADGE_HOLDERS_SEED, coin.meme_mint.as_ref()], &safe_pump::ID);
+    let mut holders = BadgeHolders::zeroed();
+    holders.meme_mint = coin.meme_mint;
+    holders.bump = bump;
+    test.add_account(holders_address, zero_copy_account(&holders));
+    test.add_account(badge_holder_page_address(&coin.meme_mint, 0), holder_page_account(&coin.meme_mint, 0, &[]));
+
+    test.add_account(coin.meme_mint, mint_account(&Pubkey::new_unique(), 6));
+    coin
+}
+
+/// Creates the coin's treasury and LP-tax vaults and the shared rewards vault through
+/// the program's own init instructions.
+pub async fn init_vaults(ctx: &mut ProgramTestContext, coin: &Coin) {
+    let vaults = ix(
+        safe_pump::accounts::InitializeVaults {
+            global_state: global_state_address(),
+            meme_mint: coin.meme_mint,
+            registry_entry: pda(&[REGISTRY_ENTRY_SEED, coin.meme_mint.as_ref()]),
+            treasury_vault: coin.treasury_vault(),
+            lp_vault: coin.lp_vault(),
+            wsol_mint: native_mint::ID,
+            payer: ctx.payer.pubkey(),
+            token_program: spl_token::ID,
+            system_program: solana_sdk::system_program::ID,
+            rent: sysvar::rent::ID,
+        },
+        safe_pump::instruction::InitializeVaults {},
+    );
+    let rewards_vault = ix(
+        safe_pump::accounts::InitializeRewardsVault {
+            global_state: global_state_address(),
+            rewards_vault: pda(&[REWARDS_VAULT_SEED]),
+            wsol_mint: native_mint::ID,
+            authority: coin.admin.pubkey(),
+            payer: ctx.payer.pubkey(),
+            token_program: spl_token::ID,
+            system_program: solana_sdk::system_program::ID,
+            rent: sysvar::rent::ID,
+        },
+        safe_pump::instruction::InitializeRewardsVault {},
+    );
+    send(ctx, &[vaults, signed_by(rewards_vault, &coin.admin.pubkey())], &[&coin.admin]).await.unwrap();
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum AuthMode {
+    Ed25519,
+    Bn254,
+    #[cfg(feature = "insecure-no-auth-swaps")]
+    NoAuth,
+}
+
+pub const AUTH_MODES: &[AuthMode] = &[
+    AuthMode::Ed25519,
+    AuthMode::Bn254,
+    #[cfg(feature = "insecure-no-auth-swaps")]
+    AuthMode::NoAuth,
+];
+
+/// A vault key of one VaultAuthKey mode, with its secret so tests can sign for it.
+pub enum VaultSigner {
+    Ed25519(Keypair),
+    Bn254(Fr),
+    #[cfg(feature = "insecure-no-auth-swaps")]
+    NoAuth,
+}
+
+/// EIP-197 encoding of a G2 point: x_im || x_re || y_im || y_re, big-endian.
+pub fn encode_g2(point: &G2Affine) -> [u8; 128] {
+    let (x, y) = point.xy().unwrap();
+    let mut out = [0u8; 128];
+    for (chunk, coord) in out.chunks_mut(32).zip([x.c1, x.c0, y.c1, y.c0]) {
+        chunk.copy_from_slice(&coord.into_bigint().to_bytes_be());
+    }
+    out
+}
+
+impl VaultSigner {
+    pub fn new(mode: AuthMode) -> Self {
+        match mode {
+            AuthMode::Ed25519 => VaultSigner::Ed25519(Keypair::new()),
+            AuthMode::Bn254 => VaultSigner::Bn254(Fr::from_be_bytes_mod_order(&Keypair::new().to_bytes()[..32])),
+            #[cfg(feature = "insecure-no-auth-swaps")]
+            AuthMode::NoAuth => VaultSigner::NoAuth,
+        }
+    }
+
+    pub fn auth_key(&self) -> VaultAuthKey {
+        match self {
+            VaultSigner::Ed25519(key) => VaultAuthKey::Ed25519(key.pubkey().to_bytes()),
+            VaultSigner::Bn254(secret) => VaultAuthKey::Bn254(encode_g2(&(G2Affine::generator() * secret).into_affine())),
+            #[cfg(feature = "insecure-no-auth-swaps")]
+            VaultSigner::NoAuth => VaultAuthKey::NoAuth,
+        }
+    }
+
+    /// Signs `msg` under `dst`. Ed25519 signatures travel in a precompile instruction
+    /// placed before the program's; the others in the instruction's signature argument.
+    pub fn sign(&self, msg: &[u8], dst: &[u8]) -> (Vec<Instruction>, Vec<u8>) {
+        match self {
+            VaultSigner::Ed25519(key) => (vec![ed25519_sign(key, &[dst, msg].concat())], vec![]),
+            VaultSigner::Bn254(secret) => {
+                let h = hash_to_g1(msg, dst).unwrap();
+                (vec![], alt_bn128_multiplication(&[&h[..], &secret.into_bigint().to_bytes_be()].concat()).unwrap())
+            }
+            #[cfg(feature = "insecure-no-auth-swaps")]
+            VaultSigner::NoAuth => (vec![], vec![]),
+        }
+    }
+}
+
+/// A trader with a registered vault key and `wsol` wrapped SOL to pay tax from.
+pub struct Trader {
+    pub user: Keypair,
+    pub vault_key: VaultSigner,
+    pub user_sol: Pubkey,
+}
+
+/// A trader with an Ed25519 vault key.
+pub async fn new_trader(ctx: &mut ProgramTestContext, wsol: u64) -> Trader {
+    new_trader_with_key(ctx, AuthMode::Ed25519, wsol).await
+}
+
+pub async fn new_trader_with_key(ctx: &mut ProgramTestContext, mode: AuthMode, wsol: u64) -> Trader {
+    let user = funded_keypair(ctx).await;
+    let vault_key = VaultSigner::new(mode);
+    let auth_key = vault_key.auth_key();
+    let (mut ixs, pop_sig) = vault_key.sign(&vault_key_pop_message(&user.pubkey(), &auth_key), VAULT_KEY_POP_DST);
+    ixs.push(ix(
+        safe_pump::accounts::RegisterVault {
+            vault: pda(&[VAULT_SEED, user.pubkey().as_ref()]),
+            user_state: pda(&[b"user-swap-data", user.pubkey().as_ref()]),
+            user: user.pubkey(),
+            instructions: sysvar::instructions::ID,
+            system_program: solana_sdk::system_program::ID,
+        },
+        safe_pump::instruction::RegisterVault { auth_key, pop_sig },
+    ));
+    send(ctx, &ixs, &[&user]).await.unwrap();
+
+    let user_sol = Pubkey::new_unique();
+    ctx.set_account(&user_sol, &wsol_account(&user.pubkey(), wsol).into());
+    Trader { user, vault_key, user_sol }
+}
+
+/// Ed25519 precompile instruction carrying `key`'s signature over `msg`.
+pub fn ed25519_sign(key: &Keypair, msg: &[u8]) -> Instruction {
+    new_ed25519_instruction_with_signature(msg, key.sign_message(msg).as_array(), &key.pubkey().to_bytes())
+}
+
+/// Signs and sends a `global_tax_swap` for `trader` on `coin`.
+pub async fn tax_swap(ctx: &mut ProgramTestContext, coin: &Coin, trader: &Trader, amount_in: u64, is_buy: bool) -> Result<(), BanksClientError> {
+    let ixs = tax_swap_ixs(ctx, coin, trader, amount_in, is_buy).await;
+    send(ctx, &ixs, &[&trader.user]).await
+}
+
+/// A signed `global_tax_swap` for `trader` on `coin`, after any precompile instruction.
+pub async fn tax_swap_ixs(ctx: &mut ProgramTestContext, coin: &Coin, trader: &Trader, amount_in: u64, is_buy: bool) -> Vec<Instruction> {
+    let user = trader.user.pubkey();
+    let vault = pda(&[VAULT_SEED, user.as_ref()]);
+    let nonce = anchor_state::<Vault>(ctx, vault).await.nonce;
+    let epoch = anchor_state::<RewardDistribution>(ctx, pda(&[REWARDS_SEED])).await.current_epoch;
+    let slot = ctx.banks_client.get_sysvar::<Clock>().await.unwrap().slot;
+
+    let pool = Pubkey::new_unique();
+    let auth = SwapAuthorization {
+        program_id: coin.child_program_id,
+        mint: coin.meme_mint,
+        pool,
+        user,
+        is_buy,
+        amount_in,
+        min_out: 0,
+        nonce,
+        expiry_slot: slot + 100,
+    };
+    let (mut ixs, auth_sig) = trader.vault_key.sign(&auth.encode(), SWAP_AUTHORIZATION_DST);
+    ixs.push(ix(
+        safe_pump::accounts::GlobalTaxSwap {
+            global_state: global_state_address(),
+            user,
+            user_sol: trader.user_sol,
+            meme_mint: coin.meme_mint,
+            registry_entry: pda(&[REGISTRY_ENTRY_SEED, coin.meme_mint.as_ref()]),
+            vault,
+            expected_vault: vault,
+            lp_vault: coin.lp_vault(),
+            treasury_vault: coin.treasury_vault(),
+            rewards_vault: pda(&[REWARDS_VAULT_SEED]),
+            rewards: pda(&[REWARDS_SEED]),
+            reward_accrual: pda(&[REWARD_ACCRUAL_SEED, user.as_ref(), &epoch.to_le_bytes()]),
+            buy_counter: pda(&[BUY_COUNTER_SEED, coin.meme_mint.as_ref(), user.as_ref()]),
+            badge_holders: pda(&[BADGE_HOLDERS_SEED, coin.meme_mint.as_ref()]),
+            tax_schedule: pda(&[TAX_SCHEDULE_SEED]),
+            velocity: pda(&[b"velocity", &slot.to_le_bytes()]),
+            instructions: sysvar::instructions::ID,
+            token_program: spl_token::ID,
+            system_program: solana_sdk::system_program::ID,
+        },
+        safe_pump::instruction::GlobalTaxSwap { amount_in, is_buy, min_out: 0, auth_sig, nonce, pool, expiry_slot: auth.expiry_slot },
+    ));
+    ixs
+}
+
+/// Signs and sends the child-facing `swap` for `trader` on `coin`: the tax leg runs as a
+/// self-CPI into `global_tax_swap`, the Raydium leg hits the stand-in. Needs
+/// `add_raydium_program` and a TokenContract at `contract`.
+pub async fn child_swap(
+    ctx: &mut ProgramTestContext,
+    coin: &Coin,
+    trader: &Trader,
+    contract: &Pubkey,
+    amount_in: u64,
+    is_buy: bool,
+) -> Result<(), BanksClientError> {
+    let ixs = child_swap_ixs(ctx, coin, trader, contract, amount_in, is_buy).await;
+    send(ctx, &ixs, &[&trader.user]).await
+}
+
+/// The instructions `child_swap` sends.
+pub async fn child_swap_ixs(
+    ctx: &mut ProgramTestContext,
+    coin: &Coin,
+    trader: &Trader,
+    contract: &Pubkey,
+    amount_in: u64,
+    is_buy: bool,
+) -> Vec<Instruction> {
+    let user = trader.user.pubkey();
+    let vault = pda(&[VAULT_SEED, user.as_ref()]);
+    let nonce = anchor_state::<Vault>(ctx, vault).await.nonce;
+    let epoch = anchor_state::<RewardDistribution>(ctx, pda(&[REWARDS_SEED])).await.current_epoch;
+    let slot = ctx.banks_client.get_sysvar::<Clock>().await.unwrap().slot;
+
+    let (pool, user_token, token_vault, sol_vault) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+    ctx.set_account(&user_token, &token_account(&coin.meme_mint, &user, 0).into());
+    ctx.set_account(&token_vault, &token_account(&coin.meme_mint, &pool, 0).into());
+    ctx.set_account(&sol_vault, &wsol_account(&pool, 0).into());
+
+    let auth = SwapAuthorization {
+        program_id: coin.child_program_id,
+        mint: coin.meme_mint,
+        pool,
+        user,
+        is_buy,
+        amount_in,
+        min_out: 0,
+        nonce,
+        expiry_slot: slot + 100,
+    };
+    let (mut ixs, auth_sig) = trader.vault_key.sign(&auth.encode(), SWAP_AUTHORIZATION_DST);
+    ixs.push(ix(
+        safe_pump::accounts::ChildSwap {
+            user,
+            user_sol: trader.user_sol,
+            user_token,
+            mint: coin.meme_mint,
+            contract: *contract,
+            pool_state: pool,
+            token_vault,
+            sol_vault,
+            raydium_program: raydium_cp_swap::id(),
+            mothership_program: safe_pump::ID,
+            global_state: global_state_address(),
+            lp_vault: coin.lp_vault(),
+            treasury_vault: coin.treasury_vault(),
+            rewards_vault: pda(&[REWARDS_VAULT_SEED]),
+            rewards: pda(&[REWARDS_SEED]),
+            reward_accrual: pda(&[REWARD_ACCRUAL_SEED, user.as_ref(), &epoch.to_le_bytes()]),
+            buy_counter: pda(&[BUY_COUNTER_SEED, coin.meme_mint.as_ref(), user.as_ref()]),
+            badge_holders: pda(&[BADGE_HOLDERS_SEED, coin.meme_mint.as_ref()]),
+            tax_schedule: pda(&[TAX_SCHEDULE_SEED]),
+            registry_entry: pda(&[REGISTRY_ENTRY_SEED, coin.meme_mint.as_ref()]),
+            vault,
+            expected_vault: vault,
+            velocity: pda(&[b"velocity", &slot.to_le_bytes()]),
+            user_state: pda(&[b"user-swap-data", user.as_ref()]),
+            instructions: sysvar::instructions::ID,
+            token_program: spl_token::ID,
+            system_program: solana_sdk::system_program::ID,
+            clock: sysvar::clock::ID,
+        },
+        safe_pump::instruction::Swap { amount_in, is_buy, minimum_amount_out: 0, auth_sig, nonce, expiry_slot: auth.expiry_slot },
+    ));
+    ixs
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// BADGES: the coin's TokenContract, its Metaplex metadata and a low-bar BadgeConfig
+// ─────────────────────────────────────────────────────────────────────────────
+pub const MEME_NAME: &str = "Seed Coin";
+pub const MEME_SYMBOL: &str = "SEED";
+pub const MEME_URI: &str = "https://example.com/seed.json";
+
+// Bronze after one 1 SOL buy, so a single tax_swap earns a badge
+pub const TEST_BADGE_TIERS: [BadgeTier; 3] = [
+    BadgeTier { min_buy_swaps: 1, min_buy_volume: LAMPORTS_PER_SOL, weight: 1 },
+    BadgeTier { min_buy_swaps: 5, min_buy_volume: 5 * LAMPORTS_PER_SOL, weight: 2 },
+    BadgeTier { min_buy_swaps: 10, min_buy_volume: 10 * LAMPORTS_PER_SOL, weight: 4 },
+];
+
+pub fn contract_address(deployer: &Pubkey) -> Pubkey {
+    pda(&[b"contract", deployer.as_ref()])
+}
+
+/// Adds the launched coin's TokenContract (no badge edition yet), its metadata and the
+/// global BadgeConfig, plus the metadata program stand-in. Returns the deployer.
+pub fn add_badge_setup(test: &mut ProgramTest, coin: &Coin) -> Keypair {
+    metadata_program::add_metadata_program(test);
+    let deployer = Keypair::new();
+    let (contract, bump) = Pubkey::find_program_address(&[b"contract", deployer.pubkey().as_ref()], &safe_pump::ID);
+    let state = TokenContract {
+        is_initialized: true,
+        total_supply: 1_000_000_000,
+        treasury_wallet: Pubkey::new_unique(),
+        swap_count: 0,
+        total_swapped: 0,
+        vault_sol_balance: 0,
+        vault_token_balance: 0,
+        burned_tokens: 0,
+        burn_percentage: 0,
+        bond_timestamp: 0,
+        buy_cap_percentage: 0,
+        liquidity_threshold_index: 0,
+        friends_wallets: [Pubkey::default(); 4],
+        friends_amounts: [0; 4],
+        deployer_amount: 0,
+        bump,
+        badge_mint: None,
+        badge_master_edition: None,
+        badge_editions_printed: 0,
+        soulbound_badges: false,
+    };
+    test.add_account(contract, anchor_account(&state, 8 + TokenContract::INIT_SPACE));
+    test.add_account(
+        metadata_program::metadata_address(&coin.meme_mint),
+        metadata_program::metadata_account(&coin.meme_mint, MEME_NAME, MEME_SYMBOL, MEME_URI),
+    );
+
+    let (config, bump) = Pubkey::find_program_address(&[BADGE_CONFIG_SEED], &safe_pump::ID);
+    test.add_account(config, anchor_account(&BadgeConfig { tiers: TEST_BADGE_TIERS, expiry_secs: 0, bump }, 256));
+    deployer
+}
@@ -0,0 +1,12 @@
+// Local stand-in for the Raydium CP-swap program. Accepts any instruction and moves
+// nothing: the swap tests only look at the mothership's tax leg.
+use solana_program_test::{processor, ProgramTest};
+use solana_sdk::{account_info::AccountInfo, entrypoint::ProgramResult, pubkey::Pubkey};
+
+pub fn add_raydium_program(test: &mut ProgramTest) {
+    test.add_program("raydium_cp_swap", raydium_cp_swap::id(), processor!(process_instruction));
+}
+
+fn process_instruction(_program_id: &Pubkey, _accounts: &[AccountInfo], _data: &[u8]) -> ProgramResult {
+    Ok(())
+}
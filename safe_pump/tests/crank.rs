@@ -0,0 +1,145 @@
+// crank_rewards over a full 1000-holder list: four holder pages, one page per call at
+// most, paying every holder and the keeper before the cursor wraps.
+mod common;
+
+use anchor_spl::associated_token::get_associated_token_address;
+use bytemuck::Zeroable;
+use common::*;
+use safe_pump::{BadgeHolders, BadgeRecord, RewardDistribution, SafePumpError, BADGE_HOLDERS_SEED, BADGE_RECORD_SEED, REWARDS_SEED, REWARDS_VAULT_SEED};
+use solana_program_test::ProgramTestContext;
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
+
+const HOLDERS: usize = 1000;
+const PAGE_SIZE: usize = 256;
+const BATCH: u8 = 8; // three reward accounts each still fit a legacy transaction
+const PER_HOLDER: u64 = 1_000_000;
+const KEEPER_FEE: u64 = PER_HOLDER * 10 / 10_000; // add_coin's keeper_fee_bps
+
+struct Holder {
+    owner: Pubkey,
+    badge_mint: Pubkey,
+    wsol: Pubkey,
+}
+
+impl Holder {
+    fn record(&self) -> Pubkey {
+        pda(&[BADGE_RECORD_SEED, self.badge_mint.as_ref()])
+    }
+}
+
+/// A coin whose closed reward epoch owes each of `HOLDERS` weight-1 badges `PER_HOLDER`.
+async fn start() -> (ProgramTestContext, Coin, Vec<Holder>, Keypair, Pubkey) {
+    let mut test = program_test();
+    let coin = add_coin(&mut test);
+    let holders: Vec<Holder> = (0..HOLDERS)
+        .map(|_| Holder { owner: Pubkey::new_unique(), badge_mint: Pubkey::new_unique(), wsol: Pubkey::new_unique() })
+        .collect();
+    for holder in &holders {
+        let (record, bump) = Pubkey::find_program_address(&[BADGE_RECORD_SEED, holder.badge_mint.as_ref()], &safe_pump::ID);
+        let state = BadgeRecord {
+            badge_mint: holder.badge_mint,
+            meme_mint: coin.meme_mint,
+            owner: holder.owner,
+            reward_debt: 0,
+            claimed: 0,
+            soulbound: false,
+            tier: 0,
+            weight: 1,
+            bump,
+        };
+        test.add_account(record, anchor_account(&state, 8 + 32 + 32 + 32 + 16 + 8 + 1 + 1 + 8 + 1));
+        test.add_account(get_associated_token_address(&holder.owner, &holder.badge_mint), token_account(&holder.badge_mint, &holder.owner, 1));
+        test.add_account(holder.wsol, wsol_account(&holder.owner, 0));
+    }
+    let owners: Vec<Pubkey> = holders.iter().map(|h| h.owner).collect();
+    for (page, chunk) in owners.chunks(PAGE_SIZE).enumerate() {
+        test.add_account(badge_holder_page_address(&coin.meme_mint, page as u64), holder_page_account(&coin.meme_mint, page as u64, chunk));
+    }
+    test.add_account(pda(&[REWARDS_VAULT_SEED]), wsol_account(&global_state_address(), HOLDERS as u64 * PER_HOLDER));
+
+    let mut ctx = test.start_with_context().await;
+    let mut ledger: RewardDistribution = anchor_state(&mut ctx, pda(&[REWARDS_SEED])).await;
+    ledger.current_epoch = 1;
+    ctx.set_account(&pda(&[REWARDS_SEED]), &anchor_account(&ledger, 128).into());
+    let mut state = BadgeHolders::zeroed();
+    state.meme_mint = coin.meme_mint;
+    state.bump = Pubkey::find_program_address(&[BADGE_HOLDERS_SEED, coin.meme_mint.as_ref()], &safe_pump::ID).1;
+    state.holder_count = HOLDERS as u64;
+    state.total_badge_weight = HOLDERS as u64;
+    state.acc_reward_per_badge = (PER_HOLDER as u128 * 1_000_000_000_000).to_le_bytes();
+    ctx.set_account(&pda(&[BADGE_HOLDERS_SEED, coin.meme_mint.as_ref()]), &zero_copy_account(&state).into());
+
+    let keeper = funded_keypair(&mut ctx).await;
+    let keeper_wsol = Pubkey::new_unique();
+    ctx.set_account(&keeper_wsol, &wsol_account(&keeper.pubkey(), 0).into());
+    (ctx, coin, holders, keeper, keeper_wsol)
+}
+
+/// A crank call from the current cursor, with the reward accounts of every holder the
+/// batch can reach on the cursor's page.
+async fn crank_ix(ctx: &mut ProgramTestContext, coin: &Coin, holders: &[Holder], keeper: &Keypair, keeper_wsol: Pubkey) -> Instruction {
+    let state = badge_holders(ctx, &coin.meme_mint).await;
+    let start = state.crank_cursor as usize;
+    let page = state.crank_page();
+    let end = (start + BATCH as usize).min(HOLDERS).min((page as usize + 1) * PAGE_SIZE);
+    let mut crank = ix(
+        safe_pump::accounts::CrankRewards {
+            global_state: global_state_address(),
+            rewards: pda(&[REWARDS_SEED]),
+            badge_holders: pda(&[BADGE_HOLDERS_SEED, coin.meme_mint.as_ref()]),
+            badge_holder_page: badge_holder_page_address(&coin.meme_mint, page),
+            rewards_vault: pda(&[REWARDS_VAULT_SEED]),
+            keeper_wsol,
+            keeper: keeper.pubkey(),
+            token_program: spl_token::ID,
+        },
+        safe_pump::instruction::CrankRewards { batch: BATCH },
+    );
+    for holder in &holders[start..end] {
+        crank.accounts.push(AccountMeta::new(holder.record(), false));
+        crank.accounts.push(AccountMeta::new_readonly(get_associated_token_address(&holder.owner, &holder.badge_mint), false));
+        crank.accounts.push(AccountMeta::new(holder.wsol, false));
+    }
+    crank
+}
+
+#[tokio::test]
+async fn crank_pays_a_thousand_holders_across_pages_and_wraps() {
+    let (mut ctx, coin, holders, keeper, keeper_wsol) = start().await;
+
+    let mut calls = 0;
+    let mut cursors = vec![];
+    loop {
+        let crank = crank_ix(&mut ctx, &coin, &holders, &keeper, keeper_wsol).await;
+        send(&mut ctx, &[crank], &[&keeper]).await.unwrap();
+        calls += 1;
+        let state = badge_holders(&mut ctx, &coin.meme_mint).await;
+        cursors.push(state.crank_cursor);
+        if state.cranked_epoch == 1 {
+            break;
+        }
+        assert!(calls < 200, "crank never finished its pass");
+    }
+
+    // Batches stop at each page boundary, and the cursor wraps once the last page is done
+    assert_eq!(calls, 32 + 32 + 32 + 29);
+    for boundary in [256, 512, 768] {
+        assert!(cursors.contains(&boundary));
+    }
+    assert_eq!(*cursors.last().unwrap(), 0);
+
+    assert_eq!(token_balance(&mut ctx, keeper_wsol).await, HOLDERS as u64 * KEEPER_FEE);
+    for holder in &holders {
+        assert_eq!(token_balance(&mut ctx, holder.wsol).await, PER_HOLDER - KEEPER_FEE);
+    }
+    assert_eq!(token_balance(&mut ctx, pda(&[REWARDS_VAULT_SEED])).await, 0);
+
+    // Same accounts as the first call, so it needs a fresh blockhash to actually run
+    ctx.get_new_latest_blockhash().await.unwrap();
+    let again = crank_ix(&mut ctx, &coin, &holders, &keeper, keeper_wsol).await;
+    assert_custom_error(send(&mut ctx, &[again], &[&keeper]).await, 0, SafePumpError::CrankAlreadyComplete.into());
+}
@@ -0,0 +1,92 @@
+// The build.rs proof-verification guard, run against the zk-token-sdk stub it was
+// written to catch (kept under tests/fixtures, never built).
+#[allow(dead_code)]
+#[path = "../build/proof_guard.rs"]
+mod proof_guard;
+
+use proof_guard::*;
+use std::path::Path;
+
+const STUB_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/solana-zk-token-sdk-stub");
+
+#[test]
+fn flags_the_deleted_zk_sdk_stub() {
+    let found = find_noop_verify_proof(Path::new(STUB_DIR)).expect("stub should be flagged");
+    assert!(found.ends_with("src/lib.rs"));
+}
+
+#[test]
+fn flags_patch_pointing_at_the_stub() {
+    let manifest = r#"
+[dependencies]
+anchor-lang = "0.31.1"
+
+[patch.crates-io]
+solana-zk-token-sdk = { path = "solana-zk-token-sdk-stub" }
+"#;
+    let mut findings = Vec::new();
+    scan_manifest("Cargo.toml", manifest, Path::new(STUB_DIR).parent().unwrap(), &mut findings);
+    assert_eq!(findings.len(), 2, "{findings:?}");
+    assert!(findings[0].contains("patches `solana-zk-token-sdk`"));
+    assert!(findings[1].contains("no-op verify_proof"));
+}
+
+#[test]
+fn detects_each_noop_body() {
+    for body in [
+        "Ok(())",
+        "Ok(Default::default())",
+        "Ok(Self::default())",
+        "Ok(U::default())",
+        "Ok(<U as Default>::default())",
+        "Ok($name)",
+    ] {
+        let source = format!("fn verify_proof(&self) -> Result<U, ProgramError> {{\n    {body}\n}}");
+        assert!(has_noop_verify_proof(&source), "missed `{body}`");
+    }
+}
+
+#[test]
+fn accepts_real_verification() {
+    let source = r#"
+        fn verify_proof(&self) -> Result<(), ProofVerificationError> {
+            let mut transcript = self.context.new_transcript();
+            let proof: PubkeyValidityProof = self.proof.try_into()?;
+            proof.verify(&pubkey, &mut transcript).map_err(|e| e.into())
+        }
+    "#;
+    assert!(!has_noop_verify_proof(source));
+}
+
+#[test]
+fn lockfile_requires_crates_io_source() {
+    let lock = r#"
+[[package]]
+name = "solana-zk-sdk"
+version = "2.3.13"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+checksum = "00"
+
+[[package]]
+name = "spl-token-2022"
+version = "8.0.1"
+source = "git+https://github.com/example/token-2022?rev=abc#abc"
+
+[[package]]
+name = "solana-zk-token-sdk"
+version = "2.3.13"
+
+[[package]]
+name = "bytemuck"
+version = "1.18.0"
+"#;
+    let mut findings = Vec::new();
+    scan_lockfile("Cargo.lock", lock, &mut findings);
+    assert_eq!(
+        findings,
+        [
+            "Cargo.lock resolves `spl-token-2022` from outside crates.io",
+            "Cargo.lock resolves `solana-zk-token-sdk` from outside crates.io",
+        ]
+    );
+}
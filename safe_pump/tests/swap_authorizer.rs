@@ -0,0 +1,147 @@
+// register_vault, global_tax_swap and swap under each VaultAuthKey mode: Ed25519, BLS
+// over bn254 and, with `insecure-no-auth-swaps`, NoAuth. The native runs check that
+// client-side signing matches the program. The compute-unit benchmarks need the SBF
+// build:
+//
+//   cargo build-sbf [--features insecure-no-auth-swaps]
+//   cargo test -p safe_pump --test swap_authorizer [--features insecure-no-auth-swaps] -- --ignored --nocapture
+mod common;
+
+use anchor_lang::solana_program::sysvar;
+use common::{raydium_program::add_raydium_program, *};
+use safe_pump_interface::{swap_authorizer::VaultAuthKey, vault_key::{vault_key_pop_message, VAULT_KEY_POP_DST}};
+use solana_compute_budget_interface::ComputeBudgetInstruction;
+use solana_program_test::{ProgramTest, ProgramTestContext};
+use solana_sdk::{
+    instruction::Instruction,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    transaction::Transaction,
+};
+
+// Default per-instruction budget; every mode has to fit without a compute budget bump
+const MAX_INSTRUCTION_CU: u64 = 200_000;
+
+/// A fresh `mode` key and its proof of possession for `owner`'s vault, plus any
+/// instruction that has to precede the registration.
+fn sign_pop(owner: &Pubkey, mode: AuthMode) -> (Vec<Instruction>, VaultAuthKey, Vec<u8>) {
+    let signer = VaultSigner::new(mode);
+    let auth_key = signer.auth_key();
+    let (ixs, pop_sig) = signer.sign(&vault_key_pop_message(owner, &auth_key), VAULT_KEY_POP_DST);
+    (ixs, auth_key, pop_sig)
+}
+
+fn register_vault_ix(user: &Pubkey, auth_key: VaultAuthKey, pop_sig: Vec<u8>) -> Instruction {
+    ix(
+        safe_pump::accounts::RegisterVault {
+            vault: Pubkey::find_program_address(&[safe_pump::VAULT_SEED, user.as_ref()], &safe_pump::ID).0,
+            user_state: Pubkey::find_program_address(&[b"user-swap-data", user.as_ref()], &safe_pump::ID).0,
+            user: *user,
+            instructions: sysvar::instructions::ID,
+            system_program: solana_sdk::system_program::ID,
+        },
+        safe_pump::instruction::RegisterVault { auth_key, pop_sig },
+    )
+}
+
+fn register_vault_ixs(user: &Pubkey, mode: AuthMode) -> Vec<Instruction> {
+    let (mut ixs, auth_key, pop_sig) = sign_pop(user, mode);
+    ixs.push(register_vault_ix(user, auth_key, pop_sig));
+    ixs
+}
+
+#[tokio::test]
+async fn each_mode_registers_a_vault() {
+    let mut ctx = program_test().start_with_context().await;
+    for &mode in AUTH_MODES {
+        let user = funded_keypair(&mut ctx).await;
+        send(&mut ctx, &register_vault_ixs(&user.pubkey(), mode), &[&user]).await.unwrap_or_else(|e| panic!("{mode:?}: {e}"));
+    }
+}
+
+#[tokio::test]
+async fn bn254_rejects_proof_for_another_vault() {
+    let mut ctx = program_test().start_with_context().await;
+    let user = funded_keypair(&mut ctx).await;
+    // The PoP message binds the owner, so a proof made for someone else does not carry over
+    let (_, auth_key, pop_sig) = sign_pop(&Pubkey::new_unique(), AuthMode::Bn254);
+    let result = send(&mut ctx, &[register_vault_ix(&user.pubkey(), auth_key, pop_sig)], &[&user]).await;
+    assert_custom_error(result, 0, safe_pump::SafePumpError::InvalidProofOfPossession.into());
+}
+
+/// The swap fixture on `test`, which already has the Raydium stand-in: a coin with its
+/// vaults, and the TokenContract `swap` checks.
+async fn start_swaps(mut test: ProgramTest) -> (ProgramTestContext, Coin, Pubkey) {
+    let coin = add_coin(&mut test);
+    let deployer = add_badge_setup(&mut test, &coin);
+    let mut ctx = test.start_with_context().await;
+    init_vaults(&mut ctx, &coin).await;
+    (ctx, coin, contract_address(&deployer.pubkey()))
+}
+
+#[tokio::test]
+async fn each_mode_authorizes_swaps() {
+    let mut test = program_test();
+    add_raydium_program(&mut test);
+    let (mut ctx, coin, contract) = start_swaps(test).await;
+    for &mode in AUTH_MODES {
+        let trader = new_trader_with_key(&mut ctx, mode, 10 * LAMPORTS_PER_SOL).await;
+        tax_swap(&mut ctx, &coin, &trader, LAMPORTS_PER_SOL, true).await.unwrap_or_else(|e| panic!("{mode:?} global_tax_swap: {e}"));
+        child_swap(&mut ctx, &coin, &trader, &contract, LAMPORTS_PER_SOL, true).await.unwrap_or_else(|e| panic!("{mode:?} swap: {e}"));
+    }
+}
+
+/// Units the program instruction at the end of `ixs` and any before it consume.
+async fn units_consumed(ctx: &mut ProgramTestContext, ixs: Vec<Instruction>, signer: &Keypair) -> u64 {
+    let mut budgeted = vec![ComputeBudgetInstruction::set_compute_unit_limit(1_400_000)];
+    budgeted.extend(ixs);
+    let blockhash = ctx.banks_client.get_latest_blockhash().await.unwrap();
+    let tx = Transaction::new_signed_with_payer(&budgeted, Some(&ctx.payer.pubkey()), &[&ctx.payer, signer], blockhash);
+    let simulation = ctx.banks_client.simulate_transaction(tx).await.unwrap();
+    simulation.result.unwrap().unwrap();
+    simulation.simulation_details.unwrap().units_consumed
+}
+
+/// safe_pump from its SBF build, next to the native Raydium stand-in (which has no .so,
+/// so it goes in before SBF is preferred).
+fn sbf_program_test() -> ProgramTest {
+    let mut test = ProgramTest::default();
+    test.prefer_bpf(false);
+    add_raydium_program(&mut test);
+    test.prefer_bpf(true);
+    test.add_program("safe_pump", safe_pump::ID, None);
+    test
+}
+
+#[tokio::test]
+#[ignore = "needs target/deploy/safe_pump.so from `cargo build-sbf`"]
+async fn register_vault_compute_units() {
+    let mut ctx = sbf_program_test().start_with_context().await;
+    for &mode in AUTH_MODES {
+        let user = funded_keypair(&mut ctx).await;
+        let units = units_consumed(&mut ctx, register_vault_ixs(&user.pubkey(), mode), &user).await;
+        println!("register_vault {mode:?}: {units} CU");
+        assert!(units <= MAX_INSTRUCTION_CU, "{mode:?} used {units} CU");
+    }
+}
+
+#[tokio::test]
+#[ignore = "needs target/deploy/safe_pump.so from `cargo build-sbf`"]
+async fn swap_compute_units() {
+    let (mut ctx, coin, contract) = start_swaps(sbf_program_test()).await;
+    for &mode in AUTH_MODES {
+        let trader = new_trader_with_key(&mut ctx, mode, 10 * LAMPORTS_PER_SOL).await;
+
+        let ixs = tax_swap_ixs(&mut ctx, &coin, &trader, LAMPORTS_PER_SOL, true).await;
+        let tax_units = units_consumed(&mut ctx, ixs, &trader.user).await;
+        println!("global_tax_swap {mode:?}: {tax_units} CU");
+        assert!(tax_units <= MAX_INSTRUCTION_CU, "{mode:?} global_tax_swap used {tax_units} CU");
+
+        // swap carries the same authorization into its global_tax_swap CPI; what it adds
+        // on top should be the Raydium leg and account checks, not a second verification
+        let ixs = child_swap_ixs(&mut ctx, &coin, &trader, &contract, LAMPORTS_PER_SOL, true).await;
+        let swap_units = units_consumed(&mut ctx, ixs, &trader.user).await;
+        println!("swap {mode:?}: {swap_units} CU ({} over global_tax_swap)", swap_units.saturating_sub(tax_units));
+        assert!(swap_units <= MAX_INSTRUCTION_CU, "{mode:?} swap used {swap_units} CU");
+    }
+}
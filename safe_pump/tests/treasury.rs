@@ -0,0 +1,218 @@
+// Treasury tax round trip: global_tax_swap collects WSOL into the per-coin treasury
+// vault, and a timelocked withdrawal pays it out with a GlobalState-signed transfer.
+// Also the guardian's veto, and the timelock on the admin replacing the guardian.
+mod common;
+
+use common::*;
+use safe_pump::{GlobalState, SafePumpError, WithdrawalRequest};
+use solana_program_test::ProgramTestContext;
+use solana_sdk::{
+    clock::Clock,
+    instruction::Instruction,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
+
+fn withdrawal_address(id: u64) -> Pubkey {
+    pda(&[b"withdrawal", &id.to_le_bytes()])
+}
+
+fn queue_withdrawal_ix(payer: Pubkey, coin: &Coin, id: u64, amount: u64, destination: Pubkey) -> Instruction {
+    let queue = ix(
+        safe_pump::accounts::QueueWithdrawal {
+            global_state: global_state_address(),
+            withdrawal: withdrawal_address(id),
+            authority: coin.admin.pubkey(),
+            payer,
+            system_program: solana_sdk::system_program::ID,
+        },
+        safe_pump::instruction::QueueWithdrawal { meme_mint: coin.meme_mint, amount, destination },
+    );
+    signed_by(queue, &coin.admin.pubkey())
+}
+
+fn execute_withdrawal_ix(payer: Pubkey, coin: &Coin, id: u64, destination: Pubkey) -> Instruction {
+    ix(
+        safe_pump::accounts::ExecuteWithdrawal {
+            global_state: global_state_address(),
+            withdrawal: withdrawal_address(id),
+            treasury_vault: coin.treasury_vault(),
+            destination,
+            payer,
+            token_program: spl_token::ID,
+        },
+        safe_pump::instruction::ExecuteWithdrawal {},
+    )
+}
+
+fn cancel_withdrawal_ix(payer: Pubkey, id: u64, authority: &Pubkey) -> Instruction {
+    let cancel = ix(
+        safe_pump::accounts::CancelWithdrawal { global_state: global_state_address(), withdrawal: withdrawal_address(id), payer, authority: *authority },
+        safe_pump::instruction::CancelWithdrawal {},
+    );
+    signed_by(cancel, authority)
+}
+
+fn set_guardian_ix(authority: &Pubkey, guardian: Pubkey) -> Instruction {
+    let set = ix(
+        safe_pump::accounts::SetGuardian { global_state: global_state_address(), authority: *authority },
+        safe_pump::instruction::SetGuardian { guardian },
+    );
+    signed_by(set, authority)
+}
+
+fn cancel_guardian_change_ix(authority: &Pubkey) -> Instruction {
+    let cancel = ix(
+        safe_pump::accounts::SetGuardian { global_state: global_state_address(), authority: *authority },
+        safe_pump::instruction::CancelGuardianChange {},
+    );
+    signed_by(cancel, authority)
+}
+
+/// Moves the clock `secs` forward.
+async fn advance_clock(ctx: &mut ProgramTestContext, secs: i64) {
+    ctx.get_new_latest_blockhash().await.unwrap();
+    let mut clock = ctx.banks_client.get_sysvar::<Clock>().await.unwrap();
+    clock.unix_timestamp += secs;
+    ctx.set_sysvar(&clock);
+}
+
+/// Has the admin name a guardian and waits out the change. Returns the guardian.
+async fn install_guardian(ctx: &mut ProgramTestContext, coin: &Coin) -> Keypair {
+    let guardian = funded_keypair(ctx).await;
+    send(ctx, &[set_guardian_ix(&coin.admin.pubkey(), guardian.pubkey())], &[&coin.admin]).await.unwrap();
+    advance_clock(ctx, WITHDRAWAL_DELAY).await;
+    guardian
+}
+
+/// Moves the clock to the moment withdrawal `id` matures.
+async fn wait_out_withdrawal_delay(ctx: &mut ProgramTestContext, id: u64) {
+    let request: WithdrawalRequest = anchor_state(ctx, withdrawal_address(id)).await;
+    assert_eq!(request.executable_at - request.queued_at, WITHDRAWAL_DELAY);
+    // The execute that follows may repeat an earlier, timelocked attempt byte for byte
+    ctx.get_new_latest_blockhash().await.unwrap();
+    let mut clock = ctx.banks_client.get_sysvar::<Clock>().await.unwrap();
+    clock.unix_timestamp = request.executable_at;
+    ctx.set_sysvar(&clock);
+}
+
+/// Funds a coin's treasury with one 2 SOL buy and returns the treasury's cut.
+async fn collect_tax(ctx: &mut ProgramTestContext, coin: &Coin) -> u64 {
+    init_vaults(ctx, coin).await;
+    let trader = new_trader(ctx, 10 * LAMPORTS_PER_SOL).await;
+    tax_swap(ctx, coin, &trader, 2 * LAMPORTS_PER_SOL, true).await.unwrap();
+
+    // 2.5% of 2 SOL; with no badges minted yet the badge share goes to treasury too
+    let total_tax = 50_000_000;
+    let (lp, swapper, badge, treasury) = (20_000_000, 16_000_000, 4_000_000, 10_000_000);
+    assert_eq!(token_balance(ctx, trader.user_sol).await, 10 * LAMPORTS_PER_SOL - total_tax);
+    assert_eq!(token_balance(ctx, coin.lp_vault()).await, lp);
+    assert_eq!(token_balance(ctx, pda(&[safe_pump::REWARDS_VAULT_SEED])).await, swapper);
+    assert_eq!(token_balance(ctx, coin.treasury_vault()).await, treasury + badge);
+    treasury + badge
+}
+
+#[tokio::test]
+async fn tax_round_trips_from_swap_to_withdrawal() {
+    let mut test = program_test();
+    let coin = add_coin(&mut test);
+    let mut ctx = test.start_with_context().await;
+    let payer = ctx.payer.pubkey();
+    let collected = collect_tax(&mut ctx, &coin).await;
+
+    let recipient = Pubkey::new_unique();
+    let destination = Pubkey::new_unique();
+    ctx.set_account(&destination, &wsol_account(&recipient, 0).into());
+    send(&mut ctx, &[queue_withdrawal_ix(payer, &coin, 0, collected, destination)], &[&coin.admin]).await.unwrap();
+
+    let early = send(&mut ctx, &[execute_withdrawal_ix(payer, &coin, 0, destination)], &[]).await;
+    assert_custom_error(early, 0, SafePumpError::WithdrawalTimelocked.into());
+
+    wait_out_withdrawal_delay(&mut ctx, 0).await;
+    send(&mut ctx, &[execute_withdrawal_ix(payer, &coin, 0, destination)], &[]).await.unwrap();
+
+    assert_eq!(token_balance(&mut ctx, destination).await, collected);
+    assert_eq!(token_balance(&mut ctx, coin.treasury_vault()).await, 0);
+    assert!(ctx.banks_client.get_account(withdrawal_address(0)).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn withdrawal_cannot_exceed_the_treasury_share() {
+    let mut test = program_test();
+    let coin = add_coin(&mut test);
+    let mut ctx = test.start_with_context().await;
+    let payer = ctx.payer.pubkey();
+    let collected = collect_tax(&mut ctx, &coin).await;
+
+    // The LP and reward shares sit in other vaults and are out of the treasury's reach
+    let destination = Pubkey::new_unique();
+    ctx.set_account(&destination, &wsol_account(&Pubkey::new_unique(), 0).into());
+    send(&mut ctx, &[queue_withdrawal_ix(payer, &coin, 0, collected + 1, destination)], &[&coin.admin]).await.unwrap();
+    wait_out_withdrawal_delay(&mut ctx, 0).await;
+
+    let result = send(&mut ctx, &[execute_withdrawal_ix(payer, &coin, 0, destination)], &[]).await;
+    assert_custom_error(result, 0, spl_token::error::TokenError::InsufficientFunds as u32);
+    assert_eq!(token_balance(&mut ctx, coin.treasury_vault()).await, collected);
+}
+
+#[tokio::test]
+async fn admin_guardian_change_waits_out_the_withdrawal_delay() {
+    let mut test = program_test();
+    let coin = add_coin(&mut test);
+    let mut ctx = test.start_with_context().await;
+    let payer = ctx.payer.pubkey();
+    let guardian = funded_keypair(&mut ctx).await;
+
+    send(&mut ctx, &[set_guardian_ix(&coin.admin.pubkey(), guardian.pubkey())], &[&coin.admin]).await.unwrap();
+    let state: GlobalState = anchor_state(&mut ctx, global_state_address()).await;
+    assert_eq!((state.guardian, state.pending_guardian), (Pubkey::default(), Some(guardian.pubkey())));
+
+    send(&mut ctx, &[queue_withdrawal_ix(payer, &coin, 0, 1, Pubkey::new_unique())], &[&coin.admin]).await.unwrap();
+    let early = send(&mut ctx, &[cancel_withdrawal_ix(payer, 0, &guardian.pubkey())], &[&guardian]).await;
+    assert_custom_error(early, 0, SafePumpError::Unauthorized.into());
+
+    advance_clock(&mut ctx, WITHDRAWAL_DELAY).await;
+    send(&mut ctx, &[cancel_withdrawal_ix(payer, 0, &guardian.pubkey())], &[&guardian]).await.unwrap();
+    assert!(ctx.banks_client.get_account(withdrawal_address(0)).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn guardian_cancels_the_admin_replacing_it() {
+    let mut test = program_test();
+    let coin = add_coin(&mut test);
+    let mut ctx = test.start_with_context().await;
+    let payer = ctx.payer.pubkey();
+    let guardian = install_guardian(&mut ctx, &coin).await;
+
+    // A stolen admin key queues its own guardian alongside a withdrawal
+    let attacker = funded_keypair(&mut ctx).await;
+    send(&mut ctx, &[set_guardian_ix(&coin.admin.pubkey(), attacker.pubkey())], &[&coin.admin]).await.unwrap();
+    send(&mut ctx, &[queue_withdrawal_ix(payer, &coin, 0, 1, Pubkey::new_unique())], &[&coin.admin]).await.unwrap();
+
+    send(&mut ctx, &[cancel_guardian_change_ix(&guardian.pubkey())], &[&guardian]).await.unwrap();
+    ctx.get_new_latest_blockhash().await.unwrap();
+    let nothing_left = send(&mut ctx, &[cancel_guardian_change_ix(&guardian.pubkey())], &[&guardian]).await;
+    assert_custom_error(nothing_left, 0, SafePumpError::NoPendingGuardian.into());
+
+    advance_clock(&mut ctx, WITHDRAWAL_DELAY).await;
+    let state: GlobalState = anchor_state(&mut ctx, global_state_address()).await;
+    assert_eq!((state.guardian, state.pending_guardian), (guardian.pubkey(), None));
+    let vetoed = send(&mut ctx, &[cancel_withdrawal_ix(payer, 0, &attacker.pubkey())], &[&attacker]).await;
+    assert_custom_error(vetoed, 0, SafePumpError::Unauthorized.into());
+    send(&mut ctx, &[cancel_withdrawal_ix(payer, 0, &guardian.pubkey())], &[&guardian]).await.unwrap();
+}
+
+#[tokio::test]
+async fn guardian_hands_its_role_on_at_once() {
+    let mut test = program_test();
+    let coin = add_coin(&mut test);
+    let mut ctx = test.start_with_context().await;
+    let payer = ctx.payer.pubkey();
+    let guardian = install_guardian(&mut ctx, &coin).await;
+
+    let successor = funded_keypair(&mut ctx).await;
+    send(&mut ctx, &[set_guardian_ix(&guardian.pubkey(), successor.pubkey())], &[&guardian]).await.unwrap();
+    send(&mut ctx, &[queue_withdrawal_ix(payer, &coin, 0, 1, Pubkey::new_unique())], &[&coin.admin]).await.unwrap();
+    send(&mut ctx, &[cancel_withdrawal_ix(payer, 0, &successor.pubkey())], &[&successor]).await.unwrap();
+    assert!(ctx.banks_client.get_account(withdrawal_address(0)).await.unwrap().is_none());
+}
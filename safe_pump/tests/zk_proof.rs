@@ -0,0 +1,197 @@
+// register_elgamal_key against the real ZK ElGamal proof program builtin.
+mod common;
+
+use anchor_lang::{solana_program::sysvar, AccountDeserialize};
+use common::*;
+use safe_pump::{zk_proof::ProofLocation, ElGamalKey, SafePumpError, ELGAMAL_KEY_SEED};
+use solana_sdk::{
+    account::Account,
+    instruction::{Instruction, InstructionError},
+    pubkey::Pubkey,
+    rent::Rent,
+    signature::{Keypair, Signer},
+    transaction::TransactionError,
+};
+use solana_system_interface::instruction as system_instruction;
+use solana_zk_sdk::{
+    encryption::elgamal::ElGamalKeypair,
+    zk_elgamal_proof_program::{
+        self,
+        instruction::{ContextStateInfo, ProofInstruction},
+        proof_data::{PubkeyValidityProofContext, PubkeyValidityProofData, ProofType, ZkProofData},
+        state::ProofContextState,
+    },
+};
+
+fn elgamal_key_address(user: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[ELGAMAL_KEY_SEED, user.as_ref()], &safe_pump::ID).0
+}
+
+fn register_ix(user: &Pubkey, proof_account: Pubkey, proof_location: ProofLocation) -> solana_sdk::instruction::Instruction {
+    ix(
+        safe_pump::accounts::RegisterElGamalKey {
+            elgamal_key: elgamal_key_address(user),
+            user: *user,
+            proof_account,
+            system_program: solana_sdk::system_program::ID,
+        },
+        safe_pump::instruction::RegisterElgamalKey { proof_location },
+    )
+}
+
+fn error_code(error: SafePumpError) -> u32 {
+    error.into()
+}
+
+/// Instructions creating a context state account and verifying `proof` into it for `authority`.
+fn verify_into_context_ixs(payer: &Pubkey, context_account: &Pubkey, proof: &PubkeyValidityProofData, authority: &Pubkey) -> [Instruction; 2] {
+    let space = std::mem::size_of::<ProofContextState<PubkeyValidityProofContext>>();
+    let create = system_instruction::create_account(
+        payer,
+        context_account,
+        Rent::default().minimum_balance(space),
+        space as u64,
+        &zk_elgamal_proof_program::id(),
+    );
+    let verify = ProofInstruction::VerifyPubkeyValidity.encode_verify_proof(
+        Some(ContextStateInfo { context_state_account: context_account, context_state_authority: authority }),
+        proof,
+    );
+    [create, verify]
+}
+
+/// Creates a context state account owned by `authority` through the proof program.
+async fn verify_into_context(ctx: &mut solana_program_test::ProgramTestContext, proof: &PubkeyValidityProofData, authority: &Pubkey) -> Pubkey {
+    let context_account = Keypair::new();
+    let ixs = verify_into_context_ixs(&ctx.payer.pubkey(), &context_account.pubkey(), proof, authority);
+    send(ctx, &ixs, &[&context_account]).await.unwrap();
+    context_account.pubkey()
+}
+
+#[tokio::test]
+async fn registers_key_verified_in_the_same_transaction() {
+    let mut ctx = program_test().start_with_context().await;
+    let user = funded_keypair(&mut ctx).await;
+    let elgamal = ElGamalKeypair::new_rand();
+    let proof = PubkeyValidityProofData::new(&elgamal).unwrap();
+
+    let context_account = Keypair::new();
+    let [create, verify] = verify_into_context_ixs(&user.pubkey(), &context_account.pubkey(), &proof, &user.pubkey());
+    let register = register_ix(&user.pubkey(), sysvar::instructions::ID, ProofLocation::InstructionOffset(-1));
+    send(&mut ctx, &[create, verify, register], &[&user, &context_account]).await.unwrap();
+
+    let account = ctx.banks_client.get_account(elgamal_key_address(&user.pubkey())).await.unwrap().unwrap();
+    let key = ElGamalKey::try_deserialize(&mut account.data.as_slice()).unwrap();
+    assert_eq!(key.user, user.pubkey());
+    assert_eq!(key.pubkey, bytemuck::bytes_of(&proof.context_data().pubkey));
+}
+
+#[tokio::test]
+async fn rejects_tampered_proof() {
+    let mut ctx = program_test().start_with_context().await;
+    let user = funded_keypair(&mut ctx).await;
+    let proof = PubkeyValidityProofData::new(&ElGamalKeypair::new_rand()).unwrap();
+
+    let mut verify = ProofInstruction::VerifyPubkeyValidity.encode_verify_proof(None, &proof);
+    // Flip a bit in the proof itself; the context (the key) is left intact
+    *verify.data.last_mut().unwrap() ^= 1;
+    let register = register_ix(&user.pubkey(), sysvar::instructions::ID, ProofLocation::InstructionOffset(-1));
+    let result = send(&mut ctx, &[verify, register], &[&user]).await;
+
+    assert!(matches!(
+        result.expect_err("tampered proof must not verify").unwrap(),
+        TransactionError::InstructionError(0, InstructionError::InvalidInstructionData)
+    ));
+    assert!(ctx.banks_client.get_account(elgamal_key_address(&user.pubkey())).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn rejects_proof_in_the_same_transaction_without_a_context() {
+    let mut ctx = program_test().start_with_context().await;
+    let user = funded_keypair(&mut ctx).await;
+    let proof = PubkeyValidityProofData::new(&ElGamalKeypair::new_rand()).unwrap();
+
+    let verify = ProofInstruction::VerifyPubkeyValidity.encode_verify_proof(None, &proof);
+    let register = register_ix(&user.pubkey(), sysvar::instructions::ID, ProofLocation::InstructionOffset(-1));
+    let result = send(&mut ctx, &[verify, register], &[&user]).await;
+    assert_custom_error(result, 1, error_code(SafePumpError::ZkProofAuthorityMismatch));
+}
+
+#[tokio::test]
+async fn rejects_proof_in_the_same_transaction_for_another_authority() {
+    let mut ctx = program_test().start_with_context().await;
+    let user = funded_keypair(&mut ctx).await;
+    // Someone else's proof replayed verbatim, context authority and all
+    let victim = Pubkey::new_unique();
+    let proof = PubkeyValidityProofData::new(&ElGamalKeypair::new_rand()).unwrap();
+
+    let context_account = Keypair::new();
+    let [create, verify] = verify_into_context_ixs(&user.pubkey(), &context_account.pubkey(), &proof, &victim);
+    let register = register_ix(&user.pubkey(), sysvar::instructions::ID, ProofLocation::InstructionOffset(-1));
+    let result = send(&mut ctx, &[create, verify, register], &[&user, &context_account]).await;
+    assert_custom_error(result, 2, error_code(SafePumpError::ZkProofAuthorityMismatch));
+    assert!(ctx.banks_client.get_account(elgamal_key_address(&user.pubkey())).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn rejects_offset_not_pointing_at_the_proof_program() {
+    let mut ctx = program_test().start_with_context().await;
+    let user = funded_keypair(&mut ctx).await;
+    // Offset 0 is the register instruction itself
+    let register = register_ix(&user.pubkey(), sysvar::instructions::ID, ProofLocation::InstructionOffset(0));
+    let result = send(&mut ctx, &[register], &[&user]).await;
+    assert_custom_error(result, 0, error_code(SafePumpError::ZkProofProgramMismatch));
+}
+
+#[tokio::test]
+async fn registers_key_from_own_context_state() {
+    let mut ctx = program_test().start_with_context().await;
+    let user = funded_keypair(&mut ctx).await;
+    let proof = PubkeyValidityProofData::new(&ElGamalKeypair::new_rand()).unwrap();
+    let context_account = verify_into_context(&mut ctx, &proof, &user.pubkey()).await;
+
+    let register = register_ix(&user.pubkey(), context_account, ProofLocation::ContextStateAccount);
+    send(&mut ctx, &[register], &[&user]).await.unwrap();
+
+    let account = ctx.banks_client.get_account(elgamal_key_address(&user.pubkey())).await.unwrap().unwrap();
+    let key = ElGamalKey::try_deserialize(&mut account.data.as_slice()).unwrap();
+    assert_eq!(key.pubkey, bytemuck::bytes_of(&proof.context_data().pubkey));
+}
+
+#[tokio::test]
+async fn rejects_context_state_of_another_authority() {
+    let mut ctx = program_test().start_with_context().await;
+    let user = funded_keypair(&mut ctx).await;
+    let someone_else = Pubkey::new_unique();
+    let proof = PubkeyValidityProofData::new(&ElGamalKeypair::new_rand()).unwrap();
+    let context_account = verify_into_context(&mut ctx, &proof, &someone_else).await;
+
+    let register = register_ix(&user.pubkey(), context_account, ProofLocation::ContextStateAccount);
+    let result = send(&mut ctx, &[register], &[&user]).await;
+    assert_custom_error(result, 0, error_code(SafePumpError::ZkProofAuthorityMismatch));
+}
+
+#[tokio::test]
+async fn rejects_context_state_not_written_by_the_proof_program() {
+    let mut test = program_test();
+    let user = Keypair::new();
+    let proof = PubkeyValidityProofData::new(&ElGamalKeypair::new_rand()).unwrap();
+    // Byte-for-byte a valid context, but owned by the system program: nothing verified it
+    let forged = Pubkey::new_unique();
+    test.add_account(
+        forged,
+        Account {
+            lamports: 1_000_000_000,
+            data: ProofContextState::encode(&user.pubkey(), ProofType::PubkeyValidity, proof.context_data()),
+            owner: solana_sdk::system_program::ID,
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+    let mut ctx = test.start_with_context().await;
+    fund(&mut ctx, &user.pubkey()).await;
+
+    let register = register_ix(&user.pubkey(), forged, ProofLocation::ContextStateAccount);
+    let result = send(&mut ctx, &[register], &[&user]).await;
+    assert_custom_error(result, 0, error_code(SafePumpError::ZkProofProgramMismatch));
+}
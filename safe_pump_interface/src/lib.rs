@@ -1,6 +1,13 @@
 //! **Only the structs needed for CPI** – copy-paste from safe_pump
 use anchor_lang::prelude::*;
 
+pub mod swap_authorization;
+pub mod swap_authorizer;
+pub mod vault_key;
+
+#[cfg(all(feature = "insecure-no-auth-swaps", not(debug_assertions)))]
+compile_error!("`insecure-no-auth-swaps` accepts unsigned swaps and must never reach a release build");
+
 // ---------------------------------------------------------------------
 // 1. Account structs (exactly the same names / layout)
 // ---------------------------------------------------------------------
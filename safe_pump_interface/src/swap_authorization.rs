@@ -0,0 +1,54 @@
+//! Swap authorization message signed by a vault's key. Shared by safe_pump and
+//! seed_coin so both sides hash exactly the same bytes.
+use anchor_lang::prelude::*;
+
+pub const SWAP_AUTHORIZATION_VERSION: u8 = 6;
+/// Domain tag for the signature scheme. Bumped together with the version so a
+/// signature over one layout can never verify under another.
+pub const SWAP_AUTHORIZATION_DST: &[u8] = b"SAFE-PUMP-V6";
+const _: () = assert!(SWAP_AUTHORIZATION_DST[SWAP_AUTHORIZATION_DST.len() - 1] == b'0' + SWAP_AUTHORIZATION_VERSION);
+
+// version || program_id || mint || pool || user || is_buy || amount_in || min_out || nonce || expiry_slot
+pub const SWAP_AUTHORIZATION_LEN: usize = 1 + 32 * 4 + 1 + 8 * 4;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SwapAuthorization {
+    /// Child program the meme coin is registered to.
+    pub program_id: Pubkey,
+    pub mint: Pubkey,
+    /// Raydium pool the trade routes through (the pool to be created, pre-bond).
+    pub pool: Pubkey,
+    pub user: Pubkey,
+    pub is_buy: bool,
+    pub amount_in: u64,
+    pub min_out: u64,
+    pub nonce: u64,
+    /// Last slot at which the signature is accepted.
+    pub expiry_slot: u64,
+}
+
+impl SwapAuthorization {
+    pub fn encode(&self) -> [u8; SWAP_AUTHORIZATION_LEN] {
+        let mut out = [0u8; SWAP_AUTHORIZATION_LEN];
+        let mut at = 0;
+        let mut put = |bytes: &[u8]| {
+            out[at..at + bytes.len()].copy_from_slice(bytes);
+            at += bytes.len();
+        };
+        put(&[SWAP_AUTHORIZATION_VERSION]);
+        put(self.program_id.as_ref());
+        put(self.mint.as_ref());
+        put(self.pool.as_ref());
+        put(self.user.as_ref());
+        put(&[self.is_buy as u8]);
+        put(&self.amount_in.to_le_bytes());
+        put(&self.min_out.to_le_bytes());
+        put(&self.nonce.to_le_bytes());
+        put(&self.expiry_slot.to_le_bytes());
+        out
+    }
+
+    pub fn is_expired(&self, slot: u64) -> bool {
+        slot > self.expiry_slot
+    }
+}
@@ -0,0 +1,175 @@
+//! Pluggable verification of vault signatures. Everything here runs on native
+//! precompiles or syscalls; no pairing arithmetic happens in BPF.
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{ed25519_program, hash::hashv, sysvar::instructions::load_instruction_at_checked};
+use solana_bn254::{compression::prelude::alt_bn128_g1_decompress, prelude::alt_bn128_pairing};
+
+/// Checks that a vault key signed `msg` under the domain tag `dst`.
+pub trait SwapAuthorizer {
+    fn verify(&self, msg: &[u8], dst: &[u8], signature: &[u8]) -> bool;
+}
+
+/// Key a vault authorizes swaps with, tagged by scheme.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VaultAuthKey {
+    /// Verified by the Ed25519 precompile over `dst || msg`; `signature` is unused.
+    Ed25519([u8; 32]),
+    /// BLS over bn254: G2 public key in EIP-197 encoding, 64-byte G1 signatures.
+    Bn254([u8; 128]),
+    /// Accepts everything. Local tests only; release builds refuse the feature.
+    #[cfg(feature = "insecure-no-auth-swaps")]
+    NoAuth,
+}
+
+pub const VAULT_AUTH_KEY_LEN: usize = 1 + 128;
+
+impl VaultAuthKey {
+    /// `instructions` is the instructions sysvar, needed by the Ed25519 path.
+    pub fn verify(&self, instructions: &AccountInfo, msg: &[u8], dst: &[u8], signature: &[u8]) -> bool {
+        match self {
+            VaultAuthKey::Ed25519(pubkey) => Ed25519Authorizer { pubkey, instructions }.verify(msg, dst, signature),
+            VaultAuthKey::Bn254(pubkey) => Bn254BlsAuthorizer { pubkey }.verify(msg, dst, signature),
+            #[cfg(feature = "insecure-no-auth-swaps")]
+            VaultAuthKey::NoAuth => NoAuth.verify(msg, dst, signature),
+        }
+    }
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// ED25519 (native precompile + instruction introspection)
+// ─────────────────────────────────────────────────────────────────────────────
+const ED25519_OFFSETS_START: usize = 2;
+const ED25519_OFFSETS_LEN: usize = 14;
+
+pub struct Ed25519Authorizer<'a, 'info> {
+    pub pubkey: &'a [u8; 32],
+    pub instructions: &'a AccountInfo<'info>,
+}
+
+impl SwapAuthorizer for Ed25519Authorizer<'_, '_> {
+    /// Passes if any Ed25519 precompile instruction in the transaction carries a
+    /// signature by `pubkey` over `dst || msg`. The runtime has already rejected
+    /// the transaction if that signature were bad.
+    fn verify(&self, msg: &[u8], dst: &[u8], _signature: &[u8]) -> bool {
+        let count = match self.instructions.try_borrow_data() {
+            Ok(data) if data.len() >= 2 => u16::from_le_bytes([data[0], data[1]]),
+            _ => return false,
+        };
+        (0..count as usize).any(|index| {
+            let Ok(ix) = load_instruction_at_checked(index, self.instructions) else { return false };
+            ix.program_id == ed25519_program::ID && ed25519_ix_signs(&ix.data, self.pubkey, dst, msg)
+        })
+    }
+}
+
+fn ed25519_ix_signs(data: &[u8], pubkey: &[u8; 32], dst: &[u8], msg: &[u8]) -> bool {
+    let Some(&num_signatures) = data.first() else { return false };
+    let read_u16 = |at: usize| data.get(at..at + 2).map(|b| u16::from_le_bytes([b[0], b[1]]) as usize);
+    (0..num_signatures as usize).any(|i| {
+        let at = ED25519_OFFSETS_START + i * ED25519_OFFSETS_LEN;
+        let (Some(pk_offset), Some(pk_ix), Some(msg_offset), Some(msg_size), Some(msg_ix), Some(sig_ix)) =
+            (read_u16(at + 4), read_u16(at + 6), read_u16(at + 8), read_u16(at + 10), read_u16(at + 12), read_u16(at + 2))
+        else {
+            return false;
+        };
+        // Every piece must live in the precompile instruction itself, or the offsets
+        // could point at bytes we never inspect
+        let in_place = u16::MAX as usize;
+        if sig_ix != in_place || pk_ix != in_place || msg_ix != in_place || msg_size != dst.len() + msg.len() {
+            return false;
+        }
+        let signed_pk = data.get(pk_offset..pk_offset + 32);
+        let signed_msg = data.get(msg_offset..msg_offset + msg_size);
+        match (signed_pk, signed_msg) {
+            (Some(pk), Some(m)) => pk == pubkey && &m[..dst.len()] == dst && &m[dst.len()..] == msg,
+            _ => false,
+        }
+    })
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// BLS OVER BN254 (alt_bn128 syscalls)
+// ─────────────────────────────────────────────────────────────────────────────
+/// bn254 base field modulus, big-endian.
+const BN254_P: [u8; 32] = [
+    0x30, 0x64, 0x4e, 0x72, 0xe1, 0x31, 0xa0, 0x29, 0xb8, 0x50, 0x45, 0xb6, 0x81, 0x81, 0x58, 0x5d,
+    0x97, 0x81, 0x6a, 0x91, 0x68, 0x71, 0xca, 0x8d, 0x3c, 0x20, 0x8c, 0x16, 0xd8, 0x7c, 0xfd, 0x47,
+];
+
+/// G2 generator, EIP-197 encoding (x_im || x_re || y_im || y_re).
+const BN254_G2_GENERATOR: [u8; 128] = [
+    0x19, 0x8e, 0x93, 0x93, 0x92, 0x0d, 0x48, 0x3a, 0x72, 0x60, 0xbf, 0xb7, 0x31, 0xfb, 0x5d, 0x25,
+    0xf1, 0xaa, 0x49, 0x33, 0x35, 0xa9, 0xe7, 0x12, 0x97, 0xe4, 0x85, 0xb7, 0xae, 0xf3, 0x12, 0xc2,
+    0x18, 0x00, 0xde, 0xef, 0x12, 0x1f, 0x1e, 0x76, 0x42, 0x6a, 0x00, 0x66, 0x5e, 0x5c, 0x44, 0x79,
+    0x67, 0x43, 0x22, 0xd4, 0xf7, 0x5e, 0xda, 0xdd, 0x46, 0xde, 0xbd, 0x5c, 0xd9, 0x92, 0xf6, 0xed,
+    0x09, 0x06, 0x89, 0xd0, 0x58, 0x5f, 0xf0, 0x75, 0xec, 0x9e, 0x99, 0xad, 0x69, 0x0c, 0x33, 0x95,
+    0xbc, 0x4b, 0x31, 0x33, 0x70, 0xb3, 0x8e, 0xf3, 0x55, 0xac, 0xda, 0xdc, 0xd1, 0x22, 0x97, 0x5b,
+    0x12, 0xc8, 0x5e, 0xa5, 0xdb, 0x8c, 0x6d, 0xeb, 0x4a, 0xab, 0x71, 0x80, 0x8d, 0xcb, 0x40, 0x8f,
+    0xe3, 0xd1, 0xe7, 0x69, 0x0c, 0x43, 0xd3, 0x7b, 0x4c, 0xe6, 0xcc, 0x01, 0x66, 0xfa, 0x7d, 0xaa,
+];
+
+pub struct Bn254BlsAuthorizer<'a> {
+    pub pubkey: &'a [u8; 128],
+}
+
+impl SwapAuthorizer for Bn254BlsAuthorizer<'_> {
+    /// e(sig, g2) == e(H(msg), pk), checked as e(-sig, g2) · e(H(msg), pk) == 1.
+    fn verify(&self, msg: &[u8], dst: &[u8], signature: &[u8]) -> bool {
+        let Ok(sig) = <[u8; 64]>::try_from(signature) else { return false };
+        // The identity key "signs" every message with the identity signature
+        if sig == [0u8; 64] || self.pubkey == &[0u8; 128] {
+            return false;
+        }
+        let Some(h) = hash_to_g1(msg, dst) else { return false };
+
+        let mut input = [0u8; 2 * (64 + 128)];
+        input[..64].copy_from_slice(&negate_g1(&sig));
+        input[64..192].copy_from_slice(&BN254_G2_GENERATOR);
+        input[192..256].copy_from_slice(&h);
+        input[256..].copy_from_slice(self.pubkey);
+        match alt_bn128_pairing(&input) {
+            Ok(out) => out.len() == 32 && out[..31].iter().all(|b| *b == 0) && out[31] == 1,
+            Err(_) => false,
+        }
+    }
+}
+
+/// Try-and-increment onto G1 (cofactor 1): x = sha256(dst || msg || ctr), kept
+/// below 2^254 so the top bits stay clear for the compression flags, and the
+/// decompression syscall rejects x >= p or non-residues. Public so off-chain signers
+/// hash exactly as the program does.
+pub fn hash_to_g1(msg: &[u8], dst: &[u8]) -> Option<[u8; 64]> {
+    (0..=u8::MAX).find_map(|ctr| {
+        let mut x = hashv(&[dst, msg, &[ctr][..]]).to_bytes();
+        x[0] &= 0x3f;
+        alt_bn128_g1_decompress(&x).ok()
+    })
+}
+
+fn negate_g1(point: &[u8; 64]) -> [u8; 64] {
+    let mut out = *point;
+    if point[32..].iter().all(|b| *b == 0) {
+        return out;
+    }
+    // y' = p - y, big-endian
+    let mut borrow = 0u16;
+    for i in (0..32).rev() {
+        let diff = BN254_P[i] as i16 - point[32 + i] as i16 - borrow as i16;
+        out[32 + i] = diff.rem_euclid(256) as u8;
+        borrow = (diff < 0) as u16;
+    }
+    out
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// NO AUTH (tests only)
+// ─────────────────────────────────────────────────────────────────────────────
+#[cfg(feature = "insecure-no-auth-swaps")]
+pub struct NoAuth;
+
+#[cfg(feature = "insecure-no-auth-swaps")]
+impl SwapAuthorizer for NoAuth {
+    fn verify(&self, _msg: &[u8], _dst: &[u8], _signature: &[u8]) -> bool {
+        true
+    }
+}
@@ -0,0 +1,24 @@
+//! Messages a vault's key signs outside of swaps: proof of possession at
+//! registration and the old key's consent to a rotation.
+use anchor_lang::prelude::*;
+
+use crate::swap_authorizer::VaultAuthKey;
+
+pub const VAULT_KEY_POP_DST: &[u8] = b"SAFE-PUMP-POP-V6";
+pub const VAULT_KEY_ROTATION_DST: &[u8] = b"SAFE-PUMP-ROTATE-V6";
+
+/// Signed by the key being registered. Binding the owner stops one wallet
+/// replaying another's proof to claim its key.
+pub fn vault_key_pop_message(user: &Pubkey, key: &VaultAuthKey) -> Vec<u8> {
+    let mut msg = user.to_bytes().to_vec();
+    key.serialize(&mut msg).expect("in-memory write");
+    msg
+}
+
+/// Signed by the outgoing key. The vault nonce makes each consent single-use.
+pub fn vault_key_rotation_message(user: &Pubkey, nonce: u64, new_key: &VaultAuthKey) -> Vec<u8> {
+    let mut msg = user.to_bytes().to_vec();
+    msg.extend_from_slice(&nonce.to_le_bytes());
+    new_key.serialize(&mut msg).expect("in-memory write");
+    msg
+}
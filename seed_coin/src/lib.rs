@@ -4,13 +4,11 @@ use anchor_spl::{
     associated_token::AssociatedToken,
     token::{Mint, Token, TokenAccount, MintTo, Transfer},
 };
-use solana_program::clock::Clock;
+use solana_program::{clock::Clock, program_option::COption, sysvar};
 use raydium_cp_swap::cpi::{accounts::{CreatePool, SwapBaseIn}, create_pool, swap_base_in};
 use raydium_cp_swap::instruction::SwapBaseInput;
 
-// BLS12-381
-use blstrs::{G1Affine, G1Projective, G2Projective};
-use subtle::CtOption;
+use safe_pump_interface::swap_authorization::{SwapAuthorization, SWAP_AUTHORIZATION_DST};
 
 // INJECTED AT COMPILE TIME
 declare_id!(CymD4HzxTN2SK6UDrCcXD2uAFk4RptvQKzMT5P9GSr32(concat!(env!("OUT_DIR"), "/generated_program_ids.rs"));
@@ -79,13 +77,6 @@ pub struct UserSwapData {
     pub vault: Pubkey,
 }
 
-#[account]
-pub struct Vault {
-    pub bump: u8,
-    pub nonce: u64,
-    pub last_signer: Pubkey,
-}
-
 // ─────────────────────────────────────────────────────────────────────────────
 // ERRORS
 // ─────────────────────────────────────────────────────────────────────────────
@@ -102,23 +93,14 @@ pub enum ChildError {
     #[msg("Exceeds Fib-tier buy cap")] ExceedsFibBuy,
     #[msg("Exceeds Fib-tier sell cap")] ExceedsFibSell,
     #[msg("Exceeds velocity limit")] ExceedsVelocityLimit,
-    #[msg("Invalid BLS signature")] InvalidBlsSignature,
+    #[msg("Invalid swap authorization signature")] InvalidSwapSignature,
     #[msg("Invalid nonce")] InvalidNonce,
     #[msg("Vault not registered")] VaultNotRegistered,
     #[msg("Invalid top-tier MCAP")] InvalidTopTierMcap,
     #[msg("Invalid cooldown")] InvalidCooldown,
     #[msg("Math overflow")] MathError,
-}
-
-// ─────────────────────────────────────────────────────────────────────────────
-// BLS VERIFICATION — v5 HASH
-// ─────────────────────────────────────────────────────────────────────────────
-fn verify_bls_sig(sig: [u8; 96], pk: [u8; 48], msg: &[u8]) -> bool {
-    let sig = match G2Projective::from_compressed(&sig) { CtOption::Some(s) => s, _ => return false };
-    let pk = match G1Affine::from_compressed(&pk) { CtOption::Some(p) => p, _ => return false };
-    let h = G2Projective::hash_to_curve(msg, b"SAFE-PUMP-V5", &[]).to_affine();
-    let g1 = G1Projective::generator().to_affine();
-    blstrs::pairing(&g1, &sig.to_affine()) == blstrs::pairing(&pk.into(), &h)
+    #[msg("Mint authority must be the contract PDA")] InvalidMintAuthority,
+    #[msg("Swap authorization has expired")] SwapAuthorizationExpired,
 }
 
 macro_rules! require_spmp_suffix {
@@ -181,14 +163,25 @@ pub mod safe_pump_child {
             contract.friends_amounts[i] = *a;
         }
 
+        // Sign with our child-authority PDA so the mothership can attribute the registration to us
         safe_pump::cpi::handshake(
-            CpiContext::new(
+            CpiContext::new_with_signer(
                 ctx.accounts.mothership_program.to_account_info(),
                 safe_pump::cpi::accounts::Handshake {
                     deployer: ctx.accounts.deployer.to_account_info(),
+                    child_authority: ctx.accounts.child_authority.to_account_info(),
+                    global_state: ctx.accounts.global_state.to_account_info(),
                     meme_mint: ctx.accounts.mint.to_account_info(),
-                    registry: ctx.accounts.registry.to_account_info(),
+                    registry_index: ctx.accounts.registry_index.to_account_info(),
+                    legacy_registry: ctx.accounts.legacy_registry.to_account_info(),
+                    registry_page: ctx.accounts.registry_page.to_account_info(),
+                    registry_entry: ctx.accounts.registry_entry.to_account_info(),
+                    badge_holders: ctx.accounts.badge_holders.to_account_info(),
+                    badge_holder_page: ctx.accounts.badge_holder_page.to_account_info(),
+                    system_program: ctx.accounts.system_program.to_account_info(),
+                    rent: ctx.accounts.rent.to_account_info(),
                 },
+                &[&[safe_pump::CHILD_AUTHORITY_SEED, &[ctx.bumps.child_authority]]],
             ),
             ctx.program_id,
         )?;
@@ -201,9 +194,9 @@ pub mod safe_pump_child {
         amount_in: u64,
         is_buy: bool,
         minimum_amount_out: u64,
-        bls_sig: [u8; 96],
-        bls_pk: [u8; 48],
+        auth_sig: Vec<u8>,
         nonce: u64,
+        expiry_slot: u64,
     ) -> Result<()> {
         require_spmp_suffix!(ctx.accounts.mint);
 
@@ -221,17 +214,26 @@ pub mod safe_pump_child {
             ChildError::SellCooldownNotMet
         );
 
-        // ZK Vault + BLS
+        // ZK Vault + signature over the swap authorization
         require_keys_eq!(ctx.accounts.vault.key(), ctx.accounts.user_state.vault, ChildError::VaultNotRegistered);
         require!(ctx.accounts.vault.nonce == nonce, ChildError::InvalidNonce);
 
-        let mut msg = Vec::new();
-        msg.extend_from_slice(&amount_in.to_le_bytes());
-        msg.push(if is_buy { 1 } else { 0 });
-        msg.extend_from_slice(&minimum_amount_out.to_le_bytes());
-        msg.extend_from_slice(&nonce.to_le_bytes());
-        msg.extend_from_slice(ctx.accounts.user.key().as_ref());
-        require!(verify_bls_sig(bls_sig, bls_pk, &msg), ChildError::InvalidBlsSignature);
+        let auth = SwapAuthorization {
+            program_id: crate::ID,
+            mint: ctx.accounts.mint.key(),
+            pool: ctx.accounts.pool_state.key(),
+            user: ctx.accounts.user.key(),
+            is_buy,
+            amount_in,
+            min_out: minimum_amount_out,
+            nonce,
+            expiry_slot,
+        };
+        require!(!auth.is_expired(clock.slot), ChildError::SwapAuthorizationExpired);
+        require!(
+            ctx.accounts.vault.auth_key.verify(&ctx.accounts.instructions, &auth.encode(), SWAP_AUTHORIZATION_DST, &auth_sig),
+            ChildError::InvalidSwapSignature
+        );
         
 
       // STEALTH VAULT CAPTURE
@@ -260,21 +262,31 @@ pub mod safe_pump_child {
                     global_state: ctx.accounts.global_state.to_account_info(),
                     user: ctx.accounts.user.to_account_info(),
                     user_sol: ctx.accounts.user_sol.to_account_info(),
+                    meme_mint: ctx.accounts.mint.to_account_info(),
+                    registry_entry: ctx.accounts.registry_entry.to_account_info(),
                     vault: ctx.accounts.vault.to_account_info(),
                     expected_vault: ctx.accounts.expected_vault.to_account_info(),
                     lp_vault: ctx.accounts.lp_vault.to_account_info(),
                     treasury_vault: ctx.accounts.treasury_vault.to_account_info(),
+                    rewards_vault: ctx.accounts.rewards_vault.to_account_info(),
                     rewards: ctx.accounts.rewards.to_account_info(),
+                    reward_accrual: ctx.accounts.reward_accrual.to_account_info(),
+                    buy_counter: ctx.accounts.buy_counter.to_account_info(),
                     badge_holders: ctx.accounts.badge_holders.to_account_info(),
+                    tax_schedule: ctx.accounts.tax_schedule.to_account_info(),
                     velocity: ctx.accounts.velocity.to_account_info(),
+                    instructions: ctx.accounts.instructions.to_account_info(),
                     token_program: ctx.accounts.token_program.to_account_info(),
                     system_program: ctx.accounts.system_program.to_account_info(),
                 },
             ),
-            amount_in, is_buy, minimum_amount_out, bls_sig, bls_pk, nonce,
+            amount_in, is_buy, minimum_amount_out, auth_sig, nonce, ctx.accounts.pool_state.key(), expiry_slot,
         )?;
 
-        let net_amount = amount_in * 9750 / 10_000;
+        // Net of the mothership's live tax split — never a hard-coded rate
+        let net_amount = ctx.accounts.registry_entry
+            .tax_split(&ctx.accounts.tax_schedule, clock.unix_timestamp)
+            .net_amount(amount_in)?;
 
         // MCAP calculation
         let mcap_lamports = if contract.vault_token_balance > 0 {
@@ -374,10 +386,21 @@ pub mod safe_pump_child {
                 &[&[b"contract", ctx.accounts.deployer.key().as_ref(), &[contract.bump]]],
             ))?;
             contract.bonded = true;
+
+            safe_pump::cpi::record_bond(CpiContext::new_with_signer(
+                ctx.accounts.mothership_program.to_account_info(),
+                safe_pump::cpi::accounts::RecordBond {
+                    global_state: ctx.accounts.global_state.to_account_info(),
+                    registry_entry: ctx.accounts.registry_entry.to_account_info(),
+                    pool_state: ctx.accounts.pool_state.to_account_info(),
+                    authority: ctx.accounts.child_authority.to_account_info(),
+                },
+                &[&[safe_pump::CHILD_AUTHORITY_SEED, &[ctx.bumps.child_authority]]],
+            ))?;
         }
 
+        // Vault nonce is advanced by the mothership in global_tax_swap
         ctx.accounts.user_state.last_swap_timestamp = clock.unix_timestamp;
-        ctx.accounts.vault.nonce = nonce + 1;
 
         Ok(())
     }
@@ -391,9 +414,25 @@ pub struct InitializeContract<'info> {
     #[account(init, payer = deployer, space = 500, seeds = [b"contract", deployer.key().as_ref()], bump)]
     pub contract: Account<'info, TokenContract>,
     #[account(mut)] pub deployer: Signer<'info>,
-    #[account(mut)] pub mint: Account<'info, Mint>,
+    #[account(mut, constraint = mint.mint_authority == COption::Some(contract.key()) @ ChildError::InvalidMintAuthority)]
+    pub mint: Account<'info, Mint>,
+    /// CHECK: signing-only PDA proving CPIs to the mothership come from this program
+    #[account(seeds = [safe_pump::CHILD_AUTHORITY_SEED], bump)]
+    pub child_authority: AccountInfo<'info>,
     #[account(address = MOTHERSHIP_PROGRAM_ID)] pub mothership_program: Program<'info, safe_pump::program::SafePump>,
-    #[account(mut)] pub registry: Account<'info, safe_pump::MemeCoinRegistry>,
+    pub global_state: Account<'info, safe_pump::GlobalState>,
+    /// CHECK: registry index, created and validated by the mothership during handshake
+    #[account(mut)] pub registry_index: AccountInfo<'info>,
+    /// CHECK: legacy registry, read by the mothership during handshake
+    pub legacy_registry: AccountInfo<'info>,
+    /// CHECK: current registry page, created and validated by the mothership during handshake
+    #[account(mut)] pub registry_page: AccountInfo<'info>,
+    /// CHECK: per-mint registry entry, created by the mothership during handshake
+    #[account(mut)] pub registry_entry: AccountInfo<'info>,
+    /// CHECK: per-mint badge holder list, created by the mothership during handshake
+    #[account(mut)] pub badge_holders: AccountInfo<'info>,
+    /// CHECK: first page of the badge holder list, created by the mothership during handshake
+    #[account(mut)] pub badge_holder_page: AccountInfo<'info>,
     pub system_program: Program<'info, System>,
     pub rent: Sysvar<'info, Rent>,
 }
@@ -424,16 +463,36 @@ pub struct ChildSwap<'info> {
     #[account(address = raydium_cp_swap::id())] pub raydium_program: Program<'info, raydium_cp_swap::program::RaydiumCpSwap>,
 
     // Mothership
+    /// CHECK: signing-only PDA proving CPIs to the mothership come from this program
+    #[account(seeds = [safe_pump::CHILD_AUTHORITY_SEED], bump)]
+    pub child_authority: AccountInfo<'info>,
     #[account(address = MOTHERSHIP_PROGRAM_ID)] pub mothership_program: Program<'info, safe_pump::program::SafePump>,
     #[account(mut)] pub global_state: Account<'info, safe_pump::GlobalState>,
-    #[account(mut)] pub lp_vault: Account<'info, TokenAccount>,
-    #[account(mut)] pub treasury_vault: Account<'info, TokenAccount>,
-    #[account(mut)] pub rewards: Account<'info, safe_pump::RewardDistribution>,
-    #[account(mut)] pub badge_holders: Account<'info, safe_pump::BadgeHolders>,
-
-    // ZK Vault
-    #[account(mut, seeds = [b"zk_vault", user.key().as_ref()], bump)] pub vault: Account<'info, Vault>,
-    #[account(seeds = [b"zk_vault", user.key().as_ref()], bump)] pub expected_vault: AccountInfo<'info>,
+    #[account(mut, seeds = [safe_pump::LP_TAX_VAULT_SEED, mint.key().as_ref()], bump, seeds::program = MOTHERSHIP_PROGRAM_ID)]
+    pub lp_vault: Account<'info, TokenAccount>,
+    #[account(mut, seeds = [safe_pump::TREASURY_VAULT_SEED, mint.key().as_ref()], bump, seeds::program = MOTHERSHIP_PROGRAM_ID)]
+    pub treasury_vault: Account<'info, TokenAccount>,
+    #[account(mut, seeds = [safe_pump::REWARDS_VAULT_SEED], bump, seeds::program = MOTHERSHIP_PROGRAM_ID)]
+    pub rewards_vault: Account<'info, TokenAccount>,
+    #[account(mut, seeds = [safe_pump::REWARDS_SEED], bump = rewards.bump, seeds::program = MOTHERSHIP_PROGRAM_ID)]
+    pub rewards: Account<'info, safe_pump::RewardDistribution>,
+    /// CHECK: created on the user's first swap by the mothership
+    #[account(mut, seeds = [safe_pump::REWARD_ACCRUAL_SEED, user.key().as_ref(), &rewards.current_epoch.to_le_bytes()], bump, seeds::program = MOTHERSHIP_PROGRAM_ID)]
+    pub reward_accrual: AccountInfo<'info>,
+    /// CHECK: created on the user's first buy by the mothership
+    #[account(mut, seeds = [safe_pump::BUY_COUNTER_SEED, mint.key().as_ref(), user.key().as_ref()], bump, seeds::program = MOTHERSHIP_PROGRAM_ID)]
+    pub buy_counter: AccountInfo<'info>,
+    #[account(mut, seeds = [safe_pump::BADGE_HOLDERS_SEED, mint.key().as_ref()], bump = badge_holders.load()?.bump, seeds::program = MOTHERSHIP_PROGRAM_ID)]
+    pub badge_holders: AccountLoader<'info, safe_pump::BadgeHolders>,
+    #[account(seeds = [safe_pump::TAX_SCHEDULE_SEED], bump = tax_schedule.bump, seeds::program = MOTHERSHIP_PROGRAM_ID)]
+    pub tax_schedule: Account<'info, safe_pump::TaxSchedule>,
+    #[account(mut, seeds = [safe_pump::REGISTRY_ENTRY_SEED, mint.key().as_ref()], bump = registry_entry.bump, seeds::program = MOTHERSHIP_PROGRAM_ID)]
+    pub registry_entry: Account<'info, safe_pump::RegistryEntry>,
+
+    // ZK Vault — the mothership's, which holds the user's registered auth key
+    #[account(mut, seeds = [safe_pump::VAULT_SEED, user.key().as_ref()], bump = vault.bump, seeds::program = MOTHERSHIP_PROGRAM_ID)]
+    pub vault: Account<'info, safe_pump::Vault>,
+    #[account(seeds = [safe_pump::VAULT_SEED, user.key().as_ref()], bump, seeds::program = MOTHERSHIP_PROGRAM_ID)] pub expected_vault: AccountInfo<'info>,
 
     // Velocity
     #[account(init_if_needed, payer = user, space = 8 + 8 + 8 + 1, seeds = [b"velocity", &clock.slot.to_le_bytes()], bump)]
@@ -441,10 +500,13 @@ pub struct ChildSwap<'info> {
 
     #[account(mut)] pub user_state: Account<'info, UserSwapData>,
 
+    /// CHECK: instructions sysvar, read by the Ed25519 authorizer
+    #[account(address = sysvar::instructions::ID)]
+    pub instructions: AccountInfo<'info>,
     pub token_program: Program<'info, Token>,
     pub system_program: Program<'info, System>,
     pub clock: Sysvar<'info, Clock>,
     pub rent: Sysvar<'info, Rent>,
 }
 
-pub use safe_pump::{GlobalState, BlockSwapState, MemeCoinRegistry, RewardDistribution, BadgeHolders};
+pub use safe_pump::{GlobalState, BlockSwapState, RegistryEntry, RewardDistribution, BadgeHolders, BuyCounter, TaxSchedule};